@@ -0,0 +1,44 @@
+// Times the Vector4 and Matrix4 arithmetic that the simd feature packs into lanes.
+// Compare `cargo bench --bench vector4` with `cargo bench --bench vector4 --features simd`.
+use std::{hint::black_box, time::Instant};
+
+use renderer::{
+    Vector4,
+    matrix::{matrix::Matrix, matrix4::Matrix4},
+    vector::vector::Vector,
+};
+
+const POINTS: usize = 10_000;
+const ROUNDS: usize = 2_000;
+const RUNS: usize = 5;
+
+fn main() {
+    let transform = black_box(Matrix4::identity() * 0.5);
+    let points: Vec<Vector4> = (0..POINTS)
+        .map(|i| Vector4::new(i as f32, 1.0, -(i as f32), 1.0))
+        .collect();
+
+    // Keeps the fastest run, which is the least disturbed by whatever else the machine is doing
+    let mut best = f64::INFINITY;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut total = 0.0;
+        for _ in 0..ROUNDS {
+            for &p in black_box(&points) {
+                let q = transform * p;
+                total += (q + p - q * 0.25).dot(p);
+            }
+        }
+        black_box(total);
+
+        let nanos = start.elapsed().as_nanos() as f64 / (POINTS * ROUNDS) as f64;
+        best = best.min(nanos);
+    }
+
+    let lanes = if cfg!(feature = "simd") {
+        "packed"
+    } else {
+        "scalar"
+    };
+    println!("vector4 ({lanes}): {best:.2} ns per point");
+}
@@ -0,0 +1,214 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{self, Event};
+
+use crate::{
+    Grid,
+    frame_buffer::FrameBuffer,
+    input::Key,
+    terminal::{RawTerminal, terminal_size},
+};
+
+// Source of time for the frame loop, so tests can step through frames without sleeping
+pub trait Clock {
+    // Time since the clock was started
+    fn elapsed(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+// Draws frames at up to fps frames per second until Ctrl-C is pressed. Each frame starts
+// from a blank grid and gets the seconds since the loop started.
+// Fails with InvalidInput unless fps is positive and finite.
+pub fn run(fps: f32, frame: impl FnMut(f32, &mut Grid<char>)) -> io::Result<()> {
+    let frame_time = frame_time(fps)?;
+    let (width, height) = terminal_size();
+    let _terminal = RawTerminal::enter()?;
+
+    let mut frame_buffer = FrameBuffer::new(' ', width, height);
+    let mut clock = SystemClock::new();
+    run_frames(
+        &mut clock,
+        frame_time,
+        &mut frame_buffer,
+        &mut io::stdout(),
+        ctrl_c_pressed,
+        frame,
+    )
+}
+
+// Raw mode turns Ctrl-C into a key press instead of a signal
+fn ctrl_c_pressed() -> io::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(event) = event::read()?
+            && Key::from_event(event) == Some(Key::Interrupt)
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// Time each frame gets at fps frames per second
+fn frame_time(fps: f32) -> io::Result<Duration> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("frame rate must be positive and finite, got {fps}"),
+        )
+    };
+
+    if !(fps.is_finite() && fps > 0.0) {
+        return Err(invalid());
+    }
+
+    // Rates so small that a frame would last longer than a Duration can hold
+    Duration::try_from_secs_f32(1.0 / fps).map_err(|_| invalid())
+}
+
+fn run_frames(
+    clock: &mut impl Clock,
+    frame_time: Duration,
+    frame_buffer: &mut FrameBuffer<char>,
+    out: &mut impl Write,
+    mut should_stop: impl FnMut() -> io::Result<bool>,
+    mut frame: impl FnMut(f32, &mut Grid<char>),
+) -> io::Result<()> {
+    while !should_stop()? {
+        let start = clock.elapsed();
+
+        frame_buffer.back_mut().clear(' ');
+        frame(start.as_secs_f32(), frame_buffer.back_mut());
+
+        write!(out, "{}", frame_buffer.present())?;
+        out.flush()?;
+        frame_buffer.swap();
+
+        // Sleep off whatever is left of this frame, slow frames start the next one right away
+        if let Some(rest) = frame_time.checked_sub(clock.elapsed() - start) {
+            clock.sleep(rest);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    // Frames take no time, so the loop sleeps for the whole frame
+    #[derive(Default)]
+    struct MockClock {
+        now: Duration,
+        slept: Duration,
+    }
+
+    impl Clock for MockClock {
+        fn elapsed(&self) -> Duration {
+            self.now
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now += duration;
+            self.slept += duration;
+        }
+    }
+
+    // Runs the given number of frames, returning the time each frame was called with and
+    // everything written to the screen
+    fn run_steps(clock: &mut MockClock, fps: f32, steps: usize) -> (Vec<f32>, String) {
+        let mut frame_buffer = FrameBuffer::new(' ', 3, 1);
+        let mut out = Vec::new();
+        let mut times = Vec::new();
+        let mut remaining = steps;
+
+        run_frames(
+            clock,
+            frame_time(fps).unwrap(),
+            &mut frame_buffer,
+            &mut out,
+            || {
+                let done = remaining == 0;
+                remaining = remaining.saturating_sub(1);
+                Ok(done)
+            },
+            |time, grid| {
+                times.push(time);
+                grid.set('#', times.len() % 3, 0);
+            },
+        )
+        .unwrap();
+
+        (times, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn frame_count_test() {
+        let mut clock = MockClock::default();
+        let (times, out) = run_steps(&mut clock, 10.0, 4);
+
+        assert_eq!(times.len(), 4);
+        assert!(times.is_sorted());
+        for (i, time) in times.iter().enumerate() {
+            assert_abs_diff_eq!(*time, 0.1 * i as f32, epsilon = 1e-6);
+        }
+        assert_abs_diff_eq!(clock.slept.as_secs_f32(), 0.4, epsilon = 1e-6);
+
+        // Each frame only erases the old cell and draws the new one
+        assert_eq!(out, "\x1B[1;2H#\x1B[1;2H #\x1B[1;1H#\x1B[1;3H \x1B[1;1H #");
+    }
+
+    #[test]
+    fn no_frames_test() {
+        let mut clock = MockClock::default();
+        let (times, out) = run_steps(&mut clock, 30.0, 0);
+        assert!(times.is_empty());
+        assert!(out.is_empty());
+        assert_eq!(clock.slept, Duration::ZERO);
+    }
+
+    #[test]
+    fn frame_time_test() {
+        assert_eq!(frame_time(4.0).unwrap(), Duration::from_millis(250));
+
+        for fps in [0.0, -30.0, f32::NAN, f32::INFINITY, 1e-39] {
+            let err = frame_time(fps).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
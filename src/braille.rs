@@ -0,0 +1,69 @@
+use crate::Grid;
+
+// Bit for each dot of a braille character, indexed by [y][x] within its 2x4 block
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// Packs each 2x4 block of pixels into a single braille character
+pub fn to_braille(grid: &Grid<bool>) -> String {
+    let width = grid.width.div_ceil(2);
+    let height = grid.height.div_ceil(4);
+
+    let mut res = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let mut mask = 0;
+            for (dy, row) in DOTS.iter().enumerate() {
+                for (dx, bit) in row.iter().enumerate() {
+                    if let Some(true) = grid.get(2 * x + dx, 4 * y + dy) {
+                        mask |= bit;
+                    }
+                }
+            }
+
+            res.push(char::from_u32(0x2800 + mask).unwrap());
+        }
+        res.push('\n');
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_dot_test() {
+        let mut grid = Grid::new(false, 2, 4);
+        assert_eq!(to_braille(&grid), "\u{2800}\n");
+
+        grid.set(true, 0, 0);
+        assert_eq!(to_braille(&grid), "\u{2801}\n");
+
+        grid.set(false, 0, 0);
+        grid.set(true, 1, 3);
+        assert_eq!(to_braille(&grid), "\u{2880}\n");
+
+        grid.set(false, 1, 3);
+        grid.set(true, 0, 3);
+        assert_eq!(to_braille(&grid), "\u{2840}\n");
+    }
+
+    #[test]
+    fn full_cell_test() {
+        let grid = Grid::new(true, 2, 4);
+        assert_eq!(to_braille(&grid), "\u{28FF}\n");
+    }
+
+    #[test]
+    fn grid_size_test() {
+        // Partial blocks on the edges still get a character
+        let mut grid = Grid::new(false, 5, 6);
+        grid.set(true, 4, 5);
+
+        assert_eq!(
+            to_braille(&grid),
+            "\u{2800}\u{2800}\u{2800}\n\u{2800}\u{2800}\u{2802}\n"
+        );
+    }
+}
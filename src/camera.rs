@@ -0,0 +1,319 @@
+use crate::{
+    matrix::{
+        matrix3::Matrix3,
+        matrix4::Matrix4,
+        rotation::{Angle, Rotation},
+    },
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+// Orbiting stops this many degrees short of straight above or below the target, where the
+// up direction would line up with the view direction
+const MAX_PITCH: f32 = 89.0;
+
+// Closest an orbit camera can zoom in to its target
+const MIN_DISTANCE: f32 = 0.01;
+
+// Perspective camera that looks down -z when it isn't rotated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vector3,
+    // Euler angles in degrees, about the same axes as a model's Transform
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    pub fov: Angle,
+    pub aspect: f32,
+    pub z_near: f32,
+    pub z_far: f32,
+}
+
+impl Camera {
+    pub fn new(fov: Angle, aspect: f32, z_near: f32, z_far: f32) -> Camera {
+        Camera {
+            position: Vector3::new(0, 0, 0),
+            yaw: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            fov,
+            aspect,
+            z_near,
+            z_far,
+        }
+    }
+
+    pub fn rotation(&self) -> Matrix3 {
+        Matrix3::rotation(
+            Angle::Degrees(self.yaw),
+            Angle::Degrees(self.pitch),
+            Angle::Degrees(self.roll),
+        )
+    }
+
+    // Direction the camera is looking in
+    pub fn forward(&self) -> Vector3 {
+        -self.rotation().z
+    }
+
+    pub fn right(&self) -> Vector3 {
+        self.rotation().x
+    }
+
+    pub fn move_forward(&mut self, distance: f32) {
+        self.position += self.forward() * distance;
+    }
+
+    pub fn move_right(&mut self, distance: f32) {
+        self.position += self.right() * distance;
+    }
+
+    // Adds to the current yaw and pitch in degrees
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch += pitch;
+    }
+
+    // Takes world space to the camera's view space
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::view(
+            Angle::Degrees(self.yaw),
+            Angle::Degrees(self.pitch),
+            Angle::Degrees(self.roll),
+            self.position,
+        )
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4 {
+        Matrix4::perspective(self.fov, self.z_far, self.z_near, self.aspect)
+    }
+
+    // Takes world space straight to clip space
+    pub fn view_projection(&self) -> Matrix4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+}
+
+// Perspective camera that circles around a target and always looks at it, for inspecting
+// models. With no rotation it sits on +z of the target looking down -z.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vector3,
+    pub distance: f32,
+    // Degrees around the target about the y axis
+    pub yaw: f32,
+    // Degrees above the target
+    pub pitch: f32,
+    pub fov: Angle,
+    pub aspect: f32,
+    pub z_near: f32,
+    pub z_far: f32,
+}
+
+impl OrbitCamera {
+    pub fn new(
+        target: Vector3,
+        distance: f32,
+        fov: Angle,
+        aspect: f32,
+        z_near: f32,
+        z_far: f32,
+    ) -> OrbitCamera {
+        OrbitCamera {
+            target,
+            distance: distance.max(MIN_DISTANCE),
+            yaw: 0.0,
+            pitch: 0.0,
+            fov,
+            aspect,
+            z_near,
+            z_far,
+        }
+    }
+
+    // Where the camera is, on a sphere of radius distance around the target
+    pub fn eye(&self) -> Vector3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let direction = Vector3::new(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        );
+
+        self.target + direction * self.distance
+    }
+
+    // Direction the camera is looking in, towards the target
+    pub fn forward(&self) -> Vector3 {
+        (self.target - self.eye()).normalize()
+    }
+
+    // Adds to the current yaw and pitch in degrees, the pitch stops short of the poles
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // Moves towards the target by amount, or away from it for negative amounts, without
+    // going through it
+    pub fn zoom(&mut self, amount: f32) {
+        self.distance = (self.distance - amount).max(MIN_DISTANCE);
+    }
+
+    // Takes world space to the camera's view space
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.eye(), self.target, Vector3::new(0, 1, 0))
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4 {
+        Matrix4::perspective(self.fov, self.z_far, self.z_near, self.aspect)
+    }
+
+    // Takes world space straight to clip space
+    pub fn view_projection(&self) -> Matrix4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{Vector4, matrix::matrix::Matrix};
+
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(Angle::Degrees(60.0), 2.0, 0.1, 10.0)
+    }
+
+    #[test]
+    fn origin_view_test() {
+        let camera = camera();
+        assert_eq!(camera.view_matrix(), Matrix4::identity());
+        assert_eq!(camera.forward(), Vector3::new(0, 0, -1));
+        assert_eq!(camera.right(), Vector3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn projection_test() {
+        let camera = camera();
+
+        for point in [
+            Vector4::new(0, 0, -5, 1),
+            Vector4::new(1, -0.5, -2, 1),
+            Vector4::new(0, 0, -9.9, 1),
+        ] {
+            let ndc = (camera.view_projection() * point).cartesian();
+            assert!((-1.0..=1.0).contains(&ndc.x));
+            assert!((-1.0..=1.0).contains(&ndc.y));
+            assert!((-1.0..=1.0).contains(&ndc.z));
+        }
+
+        // Past the far plane is out of range
+        let ndc = (camera.view_projection() * Vector4::new(0, 0, -20, 1)).cartesian();
+        assert!(ndc.z > 1.0);
+    }
+
+    #[test]
+    fn movement_test() {
+        let mut camera = camera();
+        camera.move_forward(2.0);
+        camera.move_right(-1.0);
+        assert_eq!(camera.position, Vector3::new(-1, 0, -2));
+
+        // The point the camera moved to ends up at the origin of view space
+        let view = camera.view_matrix() * Vector4::new(-1, 0, -2, 1);
+        assert_abs_diff_eq!(view, Vector4::new(0, 0, 0, 1));
+    }
+
+    #[test]
+    fn rotate_test() {
+        let mut camera = camera();
+
+        // A quarter turn to the left about y looks down -x
+        camera.rotate(0.0, 90.0);
+        assert_abs_diff_eq!(camera.forward(), Vector3::new(-1, 0, 0), epsilon = 1e-6);
+
+        camera.move_forward(3.0);
+        assert_abs_diff_eq!(camera.position, Vector3::new(-3, 0, 0), epsilon = 1e-6);
+
+        // Something straight ahead stays centered on screen
+        let ndc = (camera.view_projection() * Vector4::new(-5, 0, 0, 1)).cartesian();
+        assert_abs_diff_eq!(ndc.x, 0.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(ndc.y, 0.0, epsilon = 1e-6);
+    }
+
+    fn orbit() -> OrbitCamera {
+        OrbitCamera::new(
+            Vector3::new(1, 2, 3),
+            4.0,
+            Angle::Degrees(60.0),
+            2.0,
+            0.1,
+            10.0,
+        )
+    }
+
+    #[test]
+    fn orbit_test() {
+        let mut orbit = orbit();
+        assert_abs_diff_eq!(orbit.eye(), Vector3::new(1, 2, 7));
+        assert_abs_diff_eq!(orbit.forward(), Vector3::new(0, 0, -1));
+
+        // A quarter turn moves the eye over to +x of the target
+        orbit.rotate(90.0, 0.0);
+        assert_abs_diff_eq!(orbit.eye(), Vector3::new(5, 2, 3), epsilon = 1e-5);
+        assert_abs_diff_eq!(orbit.forward(), Vector3::new(-1, 0, 0), epsilon = 1e-6);
+
+        // Looking up from below
+        orbit.rotate(-90.0, -30.0);
+        let eye = Vector3::new(0.0, -2.0, 12f32.sqrt());
+        assert_abs_diff_eq!(orbit.eye(), orbit.target + eye, epsilon = 1e-5);
+
+        // The pitch can't go over the top
+        orbit.rotate(0.0, 500.0);
+        assert_eq!(orbit.pitch, MAX_PITCH);
+        assert!(orbit.eye().y < orbit.target.y + orbit.distance);
+    }
+
+    #[test]
+    fn orbit_view_test() {
+        let mut orbit = orbit();
+        orbit.rotate(90.0, 0.0);
+
+        // The target is straight ahead at the orbit's distance
+        let target = orbit.view_matrix() * orbit.target.homogenous();
+        assert_abs_diff_eq!(target, Vector4::new(0, 0, -4, 1), epsilon = 1e-5);
+
+        let ndc = (orbit.view_projection() * orbit.target.homogenous()).cartesian();
+        assert_abs_diff_eq!(ndc.x, 0.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(ndc.y, 0.0, epsilon = 1e-6);
+
+        // Same view as a camera turned to look down -x from the eye
+        let mut camera = Camera::new(Angle::Degrees(60.0), 2.0, 0.1, 10.0);
+        camera.position = orbit.eye();
+        camera.rotate(0.0, 90.0);
+        assert_abs_diff_eq!(orbit.view_matrix(), camera.view_matrix(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn zoom_test() {
+        let mut orbit = orbit();
+        orbit.rotate(30.0, 20.0);
+        let (eye, forward) = (orbit.eye(), orbit.forward());
+
+        // Zooming only moves along the line to the target
+        orbit.zoom(1.5);
+        assert_abs_diff_eq!(orbit.distance, 2.5);
+        assert_abs_diff_eq!(orbit.forward(), forward, epsilon = 1e-6);
+        assert_abs_diff_eq!(orbit.eye(), eye + forward * 1.5, epsilon = 1e-5);
+        assert_eq!((orbit.yaw, orbit.pitch), (30.0, 20.0));
+
+        orbit.zoom(-0.5);
+        assert_abs_diff_eq!(orbit.distance, 3.0);
+
+        // Never passes through the target
+        orbit.zoom(100.0);
+        assert_eq!(orbit.distance, MIN_DISTANCE);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    Vector2, Vector4,
+    vector::{vector::Vector, vector3::Vector3},
+    vertex::Vertex,
+};
+
+// A vertex in clip space (before the perspective divide) with owned attributes,
+// since clipping creates new vertices that don't exist in the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipVertex {
+    pub pos: Vector4,
+    // Position in world space, for lighting
+    pub world: Vector3,
+    pub tex_coord: Option<Vector2>,
+    pub normal: Option<Vector3>,
+}
+
+impl ClipVertex {
+    pub fn new(pos: Vector4, world: Vector3, vertex: &Vertex) -> ClipVertex {
+        ClipVertex {
+            pos,
+            world,
+            tex_coord: vertex.tex_coord.copied(),
+            normal: vertex.normal.copied(),
+        }
+    }
+
+    fn lerp(&self, other: &ClipVertex, t: f32) -> ClipVertex {
+        let pos = self.pos.lerp(other.pos, t);
+        let world = self.world.lerp(other.world, t);
+
+        let tex_coord = match (self.tex_coord, other.tex_coord) {
+            (Some(a), Some(b)) => Some(a.lerp(b, t)),
+            _ => None,
+        };
+
+        let normal = match (self.normal, other.normal) {
+            (Some(a), Some(b)) => Some(a.lerp(b, t)),
+            _ => None,
+        };
+
+        ClipVertex {
+            pos,
+            world,
+            tex_coord,
+            normal,
+        }
+    }
+
+    // Signed distance to the near plane (z = -w), positive when in front of it
+    fn near_distance(&self) -> f32 {
+        self.pos.z + self.pos.w
+    }
+}
+
+// Clips a clip space triangle against the near plane, keeping its winding order.
+// Returns no triangles if it is fully behind, the original if fully in front,
+// and one or two triangles if it straddles the plane.
+pub fn clip_near(triangle: [ClipVertex; 3]) -> Vec<[ClipVertex; 3]> {
+    let mut polygon: Vec<ClipVertex> = Vec::new();
+
+    // Sutherland-Hodgman against a single plane
+    for i in 0..3 {
+        let current = triangle[i];
+        let next = triangle[(i + 1) % 3];
+
+        let current_distance = current.near_distance();
+        let next_distance = next.near_distance();
+
+        if current_distance >= 0.0 {
+            polygon.push(current);
+        }
+
+        // Edge crosses the plane, so add the intersection point
+        if (current_distance >= 0.0) != (next_distance >= 0.0) {
+            let t = current_distance / (current_distance - next_distance);
+            polygon.push(current.lerp(&next, t));
+        }
+    }
+
+    // Fan triangulate the clipped polygon (3 or 4 points)
+    let mut triangles = Vec::new();
+    for i in 1..polygon.len().saturating_sub(1) {
+        triangles.push([polygon[0], polygon[i], polygon[i + 1]]);
+    }
+
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    fn clip_vertex(x: f32, y: f32, z: f32, w: f32) -> ClipVertex {
+        ClipVertex {
+            pos: Vector4::new(x, y, z, w),
+            world: Vector3::new(x, y, z),
+            tex_coord: None,
+            normal: Some(Vector3::new(0, 0, 1)),
+        }
+    }
+
+    #[test]
+    fn in_front_test() {
+        let triangle = [
+            clip_vertex(0.0, 0.0, 0.5, 1.0),
+            clip_vertex(1.0, 0.0, 0.5, 1.0),
+            clip_vertex(0.0, 1.0, 0.5, 1.0),
+        ];
+
+        assert_eq!(clip_near(triangle), vec![triangle]);
+    }
+
+    #[test]
+    fn behind_test() {
+        let triangle = [
+            clip_vertex(0.0, 0.0, -2.0, 1.0),
+            clip_vertex(1.0, 0.0, -2.0, 1.0),
+            clip_vertex(0.0, 1.0, -2.0, 1.0),
+        ];
+
+        assert!(clip_near(triangle).is_empty());
+    }
+
+    #[test]
+    fn one_inside_test() {
+        let triangle = [
+            clip_vertex(0.0, 0.0, 1.0, 1.0),
+            clip_vertex(2.0, 0.0, -3.0, 1.0),
+            clip_vertex(0.0, 2.0, -3.0, 1.0),
+        ];
+
+        let clipped = clip_near(triangle);
+        assert_eq!(clipped.len(), 1);
+
+        let [a, b, c] = clipped[0];
+        assert_eq!(a, triangle[0]);
+        assert_abs_diff_eq!(b.pos, Vector4::new(1.0, 0.0, -1.0, 1.0));
+        assert_abs_diff_eq!(c.pos, Vector4::new(0.0, 1.0, -1.0, 1.0));
+    }
+
+    #[test]
+    fn straddling_test() {
+        let triangle = [
+            clip_vertex(0.0, 0.0, 1.0, 1.0),
+            clip_vertex(2.0, 0.0, 1.0, 1.0),
+            clip_vertex(0.0, 2.0, -3.0, 1.0),
+        ];
+
+        let clipped = clip_near(triangle);
+        assert_eq!(clipped.len(), 2);
+
+        // Every output vertex lies on or in front of the near plane
+        for triangle in &clipped {
+            for vertex in triangle {
+                assert!(vertex.near_distance() >= -1e-6);
+                assert_eq!(vertex.normal, Some(Vector3::new(0, 0, 1)));
+            }
+        }
+
+        let [a, b, c] = clipped[0];
+        assert_eq!(a, triangle[0]);
+        assert_eq!(b, triangle[1]);
+        assert_abs_diff_eq!(c.pos, Vector4::new(1.0, 1.0, -1.0, 1.0));
+
+        let [a, b, c] = clipped[1];
+        assert_eq!(a, triangle[0]);
+        assert_abs_diff_eq!(b.pos, Vector4::new(1.0, 1.0, -1.0, 1.0));
+        assert_abs_diff_eq!(c.pos, Vector4::new(0.0, 1.0, -1.0, 1.0));
+    }
+}
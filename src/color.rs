@@ -0,0 +1,353 @@
+use core::fmt;
+use std::{fs, io};
+
+use crate::{
+    Grid,
+    light::{Shading, ramp_char},
+    rasterizer::{Interpolate, Rasterizable},
+    vector::vector3::Vector3,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Color {
+    // Whatever color the terminal uses by default
+    #[default]
+    Default,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // The terminal's default color has no fixed value, so it's treated as black
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Color::Default => (0, 0, 0),
+            Color::Rgb(r, g, b) => (*r, *g, *b),
+        }
+    }
+
+    fn write_fg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Default => write!(f, "\x1B[39m"),
+            Color::Rgb(r, g, b) => write!(f, "\x1B[38;2;{r};{g};{b}m"),
+        }
+    }
+
+    fn write_bg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Default => write!(f, "\x1B[49m"),
+            Color::Rgb(r, g, b) => write!(f, "\x1B[48;2;{r};{g};{b}m"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    pub fn new(ch: char, fg: Color, bg: Color) -> Cell {
+        Cell { ch, fg, bg }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new(' ', Color::Default, Color::Default)
+    }
+}
+
+// Blends the channels like to_rgb sees them, the default color only stays default when every
+// vertex uses it
+impl Interpolate for Color {
+    fn barycentric_lerp(a: Color, b: Color, c: Color, weights: Vector3) -> Color {
+        if [a, b, c].iter().all(|color| *color == Color::Default) {
+            return Color::Default;
+        }
+
+        let rgb = |color: Color| {
+            let (r, g, b) = color.to_rgb();
+            Vector3::new(r as f32, g as f32, b as f32)
+        };
+        let color = Vector3::barycentric_lerp(rgb(a), rgb(b), rgb(c), weights);
+        let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+        Color::Rgb(channel(color.x), channel(color.y), channel(color.z))
+    }
+}
+
+// A character of the ramp, in the material's color scaled by the intensity
+impl Rasterizable for Cell {
+    const BACKGROUND: Cell = Cell {
+        ch: ' ',
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    const WIREFRAME: Cell = Cell {
+        ch: '#',
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    fn shaded(intensity: f32, shading: &Shading) -> Cell {
+        let color = shading.material.diffuse_color * intensity.clamp(0.0, 1.0);
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let fg = Color::Rgb(channel(color.x), channel(color.y), channel(color.z));
+
+        Cell::new(ramp_char(intensity, shading.ramp), fg, Color::Default)
+    }
+
+    // Characters are averaged like text, and colors over the covered samples
+    fn average(samples: &[Cell], count: usize, ramp: &str) -> Cell {
+        let chars: Vec<char> = samples.iter().map(|cell| cell.ch).collect();
+        let fg = average_color(samples.iter().map(|cell| cell.fg));
+        let bg = average_color(samples.iter().map(|cell| cell.bg));
+        Cell::new(char::average(&chars, count, ramp), fg, bg)
+    }
+}
+
+// Mean of the channels like to_rgb sees them, the default color only stays default when every
+// color is
+fn average_color(colors: impl Iterator<Item = Color>) -> Color {
+    let colors: Vec<Color> = colors.collect();
+    if colors.iter().all(|color| *color == Color::Default) {
+        return Color::Default;
+    }
+
+    let total: Vector3 = colors
+        .iter()
+        .map(|color| {
+            let (r, g, b) = color.to_rgb();
+            Vector3::new(r as f32, g as f32, b as f32)
+        })
+        .sum();
+    let color = total * (1.0 / colors.len() as f32);
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    Color::Rgb(channel(color.x), channel(color.y), channel(color.z))
+}
+
+// Only emits an escape sequence when the colors change from the previous cell,
+// and resets the colors at the end of each row
+impl fmt::Display for Grid<Cell> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            let mut current = (Color::Default, Color::Default);
+
+            for x in 0..self.width {
+                let Some(cell) = self.get(x, y) else {
+                    continue;
+                };
+
+                if cell.fg != current.0 {
+                    cell.fg.write_fg(f)?;
+                }
+
+                if cell.bg != current.1 {
+                    cell.bg.write_bg(f)?;
+                }
+
+                current = (cell.fg, cell.bg);
+                write!(f, "{}", cell.ch)?;
+            }
+
+            if current != (Color::Default, Color::Default) {
+                write!(f, "\x1B[0m")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Grid<Color> {
+    // Writes a binary (P6) PPM image with every cell scaled up to a cell_size square of pixels
+    pub fn to_ppm(&self, path: &str, cell_size: usize) -> io::Result<()> {
+        let width = self.width * cell_size;
+        let height = self.height * cell_size;
+        let mut data = format!("P6\n{width} {height}\n255\n").into_bytes();
+
+        for row in self.iter_rows() {
+            for _ in 0..cell_size {
+                for color in row {
+                    let (r, g, b) = color.to_rgb();
+                    for _ in 0..cell_size {
+                        data.extend([r, g, b]);
+                    }
+                }
+            }
+        }
+
+        fs::write(path, data)
+    }
+}
+
+// Packs two vertical pixels into each character using the upper half block, with the
+// top pixel as the foreground and the bottom pixel as the background
+pub fn to_half_blocks(pixels: &Grid<Color>) -> String {
+    let height = pixels.height.div_ceil(2);
+    let mut grid = Grid::new(Cell::default(), pixels.width, height);
+
+    for y in 0..height {
+        for x in 0..pixels.width {
+            let top = pixels.get(x, 2 * y).copied().unwrap_or_default();
+            let bottom = pixels.get(x, 2 * y + 1).copied().unwrap_or_default();
+            grid.set(Cell::new('▀', top, bottom), x, y);
+        }
+    }
+
+    grid.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn two_color_row_test() {
+        let red = Color::Rgb(255, 0, 0);
+        let blue = Color::Rgb(0, 0, 255);
+
+        let mut grid = Grid::new(Cell::default(), 2, 1);
+        grid.set(Cell::new('a', red, Color::Default), 0, 0);
+        grid.set(Cell::new('b', blue, red), 1, 0);
+
+        assert_eq!(
+            grid.to_string(),
+            "\x1B[38;2;255;0;0ma\x1B[38;2;0;0;255m\x1B[48;2;255;0;0mb\x1B[0m\n"
+        );
+    }
+
+    #[test]
+    fn coalesce_runs_test() {
+        let green = Color::Rgb(0, 255, 0);
+
+        let mut grid = Grid::new(Cell::new('#', green, Color::Default), 3, 2);
+        grid.set(Cell::default(), 2, 1);
+
+        assert_eq!(
+            grid.to_string(),
+            "\x1B[38;2;0;255;0m###\x1B[0m\n\x1B[38;2;0;255;0m##\x1B[39m \n"
+        );
+    }
+
+    #[test]
+    fn default_colors_test() {
+        // Uncolored cells don't emit any escape sequences
+        let grid = Grid::new(Cell::default(), 3, 1);
+        assert_eq!(grid.to_string(), "   \n");
+    }
+
+    #[test]
+    fn half_blocks_test() {
+        let red = Color::Rgb(255, 0, 0);
+        let blue = Color::Rgb(0, 0, 255);
+
+        let mut pixels = Grid::new(red, 2, 2);
+        pixels.set(blue, 0, 1);
+        pixels.set(blue, 1, 1);
+
+        assert_eq!(
+            to_half_blocks(&pixels),
+            "\x1B[38;2;255;0;0m\x1B[48;2;0;0;255m▀▀\x1B[0m\n"
+        );
+    }
+
+    #[test]
+    fn half_blocks_odd_height_test() {
+        let red = Color::Rgb(255, 0, 0);
+        let pixels = Grid::new(red, 1, 3);
+
+        // The last row has no bottom pixel so it keeps the default background
+        assert_eq!(
+            to_half_blocks(&pixels),
+            "\x1B[38;2;255;0;0m\x1B[48;2;255;0;0m▀\x1B[0m\n\x1B[38;2;255;0;0m▀\x1B[0m\n"
+        );
+    }
+
+    #[test]
+    fn ppm_test() {
+        let mut grid = Grid::new(Color::Default, 2, 2);
+        grid.set(Color::Rgb(255, 0, 0), 0, 0);
+        grid.set(Color::Rgb(0, 255, 0), 1, 0);
+        grid.set(Color::Rgb(0, 0, 255), 1, 1);
+
+        let path = env::temp_dir().join("renderer_color_grid.ppm");
+        let path = path.to_str().unwrap();
+        grid.to_ppm(path, 1).unwrap();
+        let data = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(
+            &data[header.len()..],
+            [255, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn ppm_cell_size_test() {
+        let mut grid = Grid::new(Color::Rgb(1, 2, 3), 2, 1);
+        grid.set(Color::Rgb(4, 5, 6), 1, 0);
+
+        let path = env::temp_dir().join("renderer_color_grid_scaled.ppm");
+        let path = path.to_str().unwrap();
+        grid.to_ppm(path, 2).unwrap();
+        let data = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        // Each cell becomes a 2x2 block, so both rows repeat the same pixels
+        let header = b"P6\n4 2\n255\n";
+        let row = [1, 2, 3, 1, 2, 3, 4, 5, 6, 4, 5, 6];
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(&data[header.len()..], [row, row].concat());
+    }
+
+    #[test]
+    fn interpolate_test() {
+        let colors = [Color::Rgb(255, 0, 0), Color::Rgb(0, 255, 0), Color::Default];
+        let [a, b, c] = colors;
+
+        let centroid = Vector3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0);
+        assert_eq!(
+            Color::barycentric_lerp(a, b, c, centroid),
+            Color::Rgb(85, 85, 0)
+        );
+
+        // Each vertex gets its own color back, the default one as black
+        assert_eq!(Color::barycentric_lerp(a, b, c, Vector3::new(1, 0, 0)), a);
+        assert_eq!(Color::barycentric_lerp(a, b, c, Vector3::new(0, 1, 0)), b);
+        assert_eq!(
+            Color::barycentric_lerp(a, b, c, Vector3::new(0, 0, 1)),
+            Color::Rgb(0, 0, 0)
+        );
+
+        let default = Color::Default;
+        assert_eq!(
+            Color::barycentric_lerp(default, default, default, centroid),
+            default
+        );
+    }
+
+    #[test]
+    fn average_test() {
+        let red = Cell::new('@', Color::Rgb(255, 0, 0), Color::Default);
+        let blue = Cell::new('@', Color::Rgb(0, 0, 255), Color::Default);
+
+        // Characters dim with the uncovered samples, colors only mix the covered ones
+        let cell = Cell::average(&[red, blue], 4, "a@");
+        assert_eq!(
+            cell,
+            Cell::new('@', Color::Rgb(128, 0, 128), Color::Default)
+        );
+
+        let cell = Cell::average(&[red], 4, "a@");
+        assert_eq!(cell, Cell::new('a', Color::Rgb(255, 0, 0), Color::Default));
+    }
+}
@@ -0,0 +1,774 @@
+use crate::{Grid, Vector2, rasterizer::Scissor, triangle::Triangle, vector::vector::Vector};
+
+// Clips a line segment to the inclusive (min_x, min_y, max_x, max_y) cell bounds using
+// Liang-Barsky so lines that go far off-screen don't have to be walked pixel by pixel.
+// Returns None when nothing is visible.
+fn clip_line(
+    a: Vector2,
+    b: Vector2,
+    (min_x, min_y, max_x, max_y): (usize, usize, usize, usize),
+) -> Option<(Vector2, Vector2)> {
+    let (min_x, min_y) = (min_x as f32, min_y as f32);
+    let (max_x, max_y) = (max_x as f32, max_y as f32);
+
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    // Each (p, q) pair is one of the four edges of the bounds
+    for (p, q) in [
+        (-dx, a.x - min_x),
+        (dx, max_x - a.x),
+        (-dy, a.y - min_y),
+        (dy, max_y - a.y),
+    ] {
+        if p == 0.0 {
+            // Parallel to this edge and outside of it
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t0 = f32::max(t0, t);
+        } else {
+            t1 = f32::min(t1, t);
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+    }
+
+    let start = Vector2::new(a.x + t0 * dx, a.y + t0 * dy);
+    let end = Vector2::new(a.x + t1 * dx, a.y + t1 * dy);
+    Some((start, end))
+}
+
+// Bresenham's line algorithm, works for every slope and direction
+pub fn draw_line<T: Clone>(grid: &mut Grid<T>, a: Vector2, b: Vector2, value: T) {
+    let scissor = Scissor::new(0, 0, grid.width, grid.height);
+    draw_scissored_line(grid, a, b, value, scissor);
+}
+
+// Same as draw_line, but only the part of the line inside the scissor is drawn
+pub fn draw_scissored_line<T: Clone>(
+    grid: &mut Grid<T>,
+    a: Vector2,
+    b: Vector2,
+    value: T,
+    scissor: Scissor,
+) {
+    let Some(bounds) = scissor.bounds(grid.width, grid.height) else {
+        return;
+    };
+    let Some((a, b)) = clip_line(a, b, bounds) else {
+        return;
+    };
+
+    let (mut x0, mut y0) = (a.x.round() as i64, a.y.round() as i64);
+    let (x1, y1) = (b.x.round() as i64, b.y.round() as i64);
+
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        grid.set(value.clone(), x0 as usize, y0 as usize);
+
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x0 += sx;
+        }
+
+        if e2 <= dx {
+            err += dx;
+            y0 += sy;
+        }
+    }
+}
+
+pub fn draw_wireframe<T: Clone>(grid: &mut Grid<T>, t: &Triangle, value: T) {
+    let scissor = Scissor::new(0, 0, grid.width, grid.height);
+    draw_scissored_wireframe(grid, t, value, scissor);
+}
+
+// Same as draw_wireframe, but the edges stop at the scissor
+pub fn draw_scissored_wireframe<T: Clone>(
+    grid: &mut Grid<T>,
+    t: &Triangle,
+    value: T,
+    scissor: Scissor,
+) {
+    let a = Vector2::new(t.a.pos.x, t.a.pos.y);
+    let b = Vector2::new(t.b.pos.x, t.b.pos.y);
+    let c = Vector2::new(t.c.pos.x, t.c.pos.y);
+
+    draw_scissored_line(grid, a, b, value.clone(), scissor);
+    draw_scissored_line(grid, b, c, value.clone(), scissor);
+    draw_scissored_line(grid, c, a, value, scissor);
+}
+
+// Sets every cell inside the triangle (edges included) to value, ignoring depth and lighting
+pub fn fill_triangle<T: Clone>(grid: &mut Grid<T>, t: &Triangle, value: T) {
+    let (min_x, min_y, max_x, max_y) = t.get_bounding_box(grid.width, grid.height);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if t.contains(Vector2::new(x, y)) {
+                grid.set(value.clone(), x, y);
+            }
+        }
+    }
+}
+
+// How far a curve can be from the straight lines it's drawn with, in cells
+const FLATNESS: f32 = 0.25;
+
+// Subdivisions stop here even if the curve isn't flat yet, 2^16 segments is plenty for a grid
+const MAX_DEPTH: usize = 16;
+
+// Distance from p to the segment from a to b
+fn segment_distance(p: Vector2, a: Vector2, b: Vector2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.dot(ab);
+    if length_squared == 0.0 {
+        return p.distance(a);
+    }
+
+    let t = ((p - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+// Adds the end points of line segments that follow a cubic Bezier curve to points, splitting
+// it in half (de Casteljau) until its control points are all close enough to the chord. The
+// curve stays inside of the control points, so it's then close enough as well.
+fn flatten_cubic(curve: [Vector2; 4], depth: usize, points: &mut Vec<Vector2>) {
+    let [p0, p1, p2, p3] = curve;
+
+    let flat = segment_distance(p1, p0, p3) <= FLATNESS && segment_distance(p2, p0, p3) <= FLATNESS;
+    if flat || depth >= MAX_DEPTH {
+        points.push(p3);
+        return;
+    }
+
+    let p01 = p0.lerp(p1, 0.5);
+    let p12 = p1.lerp(p2, 0.5);
+    let p23 = p2.lerp(p3, 0.5);
+    let p012 = p01.lerp(p12, 0.5);
+    let p123 = p12.lerp(p23, 0.5);
+    let middle = p012.lerp(p123, 0.5);
+
+    flatten_cubic([p0, p01, p012, middle], depth + 1, points);
+    flatten_cubic([middle, p123, p23, p3], depth + 1, points);
+}
+
+// Cubic Bezier curve from p0 to p3 pulled towards p1 and p2, drawn as short lines
+pub fn draw_bezier_cubic<T: Clone>(
+    grid: &mut Grid<T>,
+    p0: Vector2,
+    p1: Vector2,
+    p2: Vector2,
+    p3: Vector2,
+    value: T,
+) {
+    let mut points = vec![p0];
+    flatten_cubic([p0, p1, p2, p3], 0, &mut points);
+
+    for segment in points.windows(2) {
+        draw_line(grid, segment[0], segment[1], value.clone());
+    }
+}
+
+// Quadratic Bezier curve from p0 to p2 pulled towards p1, drawn as the same curve in cubic form
+pub fn draw_bezier_quadratic<T: Clone>(
+    grid: &mut Grid<T>,
+    p0: Vector2,
+    p1: Vector2,
+    p2: Vector2,
+    value: T,
+) {
+    let c1 = p0 + (p1 - p0) * (2.0 / 3.0);
+    let c2 = p2 + (p1 - p2) * (2.0 / 3.0);
+    draw_bezier_cubic(grid, p0, c1, c2, p2, value);
+}
+
+// Sets a cell given signed coordinates, anything off the grid is skipped
+fn plot<T: Clone>(grid: &mut Grid<T>, x: i64, y: i64, value: T) {
+    if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+        grid.set(value, x, y);
+    }
+}
+
+// Fills the cells from x0 to x1 (inclusive) of row y, clipped to the grid
+fn fill_span<T: Clone>(grid: &mut Grid<T>, x0: i64, x1: i64, y: i64, value: T) {
+    let (Ok(x1), Ok(y)) = (usize::try_from(x1), usize::try_from(y)) else {
+        return;
+    };
+
+    let x0 = x0.max(0) as usize;
+    if x0 <= x1 {
+        grid.fill_rect(value, x0, y, x1 - x0 + 1, 1);
+    }
+}
+
+// Calls f with the offsets (x, y) of one octant of a circle, from (r, 0) until x < y. Every
+// other point of the circle is one of these with the signs and coordinates swapped.
+fn circle_points(r: i64, mut f: impl FnMut(i64, i64)) {
+    let (mut x, mut y) = (r, 0);
+    let mut err = 1 - r;
+
+    while x >= y {
+        f(x, y);
+        y += 1;
+
+        if err < 0 {
+            err += 2 * y + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y - x) + 1;
+        }
+    }
+}
+
+// Calls f with the offsets (x, y) of one quadrant of an axis aligned ellipse, from (0, ry) to
+// (rx, 0). The decision variable of the second region is kept 4 times larger so it stays an
+// integer.
+fn ellipse_points(rx: i64, ry: i64, mut f: impl FnMut(i64, i64)) {
+    // The second region would never step along x
+    if ry == 0 {
+        (0..=rx).for_each(|x| f(x, 0));
+        return;
+    }
+
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let (mut x, mut y) = (0, ry);
+    let (mut px, mut py) = (0, 2 * rx2 * y);
+
+    // Slope is shallower than -1, x steps every time
+    let mut p = ry2 - rx2 * ry + rx2 / 4;
+    while px < py {
+        f(x, y);
+        x += 1;
+        px += 2 * ry2;
+
+        if p < 0 {
+            p += ry2 + px;
+        } else {
+            y -= 1;
+            py -= 2 * rx2;
+            p += ry2 + px - py;
+        }
+    }
+
+    // Steeper than -1, y steps every time
+    let mut p = ry2 * (2 * x + 1) * (2 * x + 1) + 4 * rx2 * (y - 1) * (y - 1) - 4 * rx2 * ry2;
+    while y >= 0 {
+        f(x, y);
+        y -= 1;
+        py -= 2 * rx2;
+
+        if p > 0 {
+            p += 4 * (rx2 - py);
+        } else {
+            x += 1;
+            px += 2 * ry2;
+            p += 4 * (rx2 - py + px);
+        }
+    }
+}
+
+// Largest radius drawn in cells. The ellipse decision variables grow with the fourth power of
+// the radius and would overflow an i64 past this.
+const MAX_RADIUS: f32 = 16384.0;
+
+// Where an ellipse lies relative to the grid, with its center and radii rounded to cells
+enum Extent {
+    // Nothing of it is on the grid
+    Outside,
+    // The whole grid is inside it, away from the outline
+    Covers,
+    Cells(i64, i64, i64, i64),
+}
+
+// Ellipses that miss the grid or cover all of it are caught here in floating point, so the
+// ones left have a center near the grid and a radius the midpoint algorithms can step through.
+// A radius past MAX_RADIUS whose outline still crosses the grid is not drawn.
+fn extent<T>(grid: &Grid<T>, center: Vector2, radii: Vector2) -> Extent {
+    // Also rejects NaN
+    if !(radii.x >= 0.0 && radii.y >= 0.0 && center.x.is_finite() && center.y.is_finite()) {
+        return Extent::Outside;
+    }
+
+    if grid.width == 0 || grid.height == 0 {
+        return Extent::Outside;
+    }
+
+    let (cx, cy) = (center.x.round() as f64, center.y.round() as f64);
+    let (rx, ry) = (radii.x.round() as f64, radii.y.round() as f64);
+    let (right, bottom) = (grid.width as f64 - 1.0, grid.height as f64 - 1.0);
+
+    if cx + rx < 0.0 || cx - rx > right || cy + ry < 0.0 || cy - ry > bottom {
+        return Extent::Outside;
+    }
+
+    // The ellipse is convex, so the grid is inside it when its corners are. Shrinking the radii
+    // by a cell keeps the rounded outline off the grid.
+    if rx > 1.0 && ry > 1.0 {
+        let inside = |x: f64, y: f64| {
+            let (dx, dy) = ((x - cx) / (rx - 1.0), (y - cy) / (ry - 1.0));
+            dx * dx + dy * dy <= 1.0
+        };
+
+        if [(0.0, 0.0), (right, 0.0), (0.0, bottom), (right, bottom)]
+            .into_iter()
+            .all(|(x, y)| inside(x, y))
+        {
+            return Extent::Covers;
+        }
+    }
+
+    if rx > MAX_RADIUS as f64 || ry > MAX_RADIUS as f64 {
+        return Extent::Outside;
+    }
+
+    Extent::Cells(cx as i64, cy as i64, rx as i64, ry as i64)
+}
+
+// Outline of a circle with the midpoint circle algorithm, clipped to the grid
+pub fn draw_circle<T: Clone>(grid: &mut Grid<T>, center: Vector2, radius: f32, value: T) {
+    let Extent::Cells(cx, cy, r, _) = extent(grid, center, Vector2::new(radius, radius)) else {
+        return;
+    };
+
+    circle_points(r, |x, y| {
+        for (x, y) in [(x, y), (y, x)] {
+            plot(grid, cx + x, cy + y, value.clone());
+            plot(grid, cx - x, cy + y, value.clone());
+            plot(grid, cx + x, cy - y, value.clone());
+            plot(grid, cx - x, cy - y, value.clone());
+        }
+    });
+}
+
+// Circle with its inside filled, covering exactly the cells inside draw_circle's outline
+pub fn fill_circle<T: Clone>(grid: &mut Grid<T>, center: Vector2, radius: f32, value: T) {
+    let (cx, cy, r) = match extent(grid, center, Vector2::new(radius, radius)) {
+        Extent::Outside => return,
+        Extent::Covers => return grid.clear(value),
+        Extent::Cells(cx, cy, r, _) => (cx, cy, r),
+    };
+
+    circle_points(r, |x, y| {
+        for (x, y) in [(x, y), (y, x)] {
+            fill_span(grid, cx - x, cx + x, cy + y, value.clone());
+            fill_span(grid, cx - x, cx + x, cy - y, value.clone());
+        }
+    });
+}
+
+// Outline of an axis aligned ellipse with radii.x across and radii.y down, clipped to the grid
+pub fn draw_ellipse<T: Clone>(grid: &mut Grid<T>, center: Vector2, radii: Vector2, value: T) {
+    let Extent::Cells(cx, cy, rx, ry) = extent(grid, center, radii) else {
+        return;
+    };
+
+    ellipse_points(rx, ry, |x, y| {
+        plot(grid, cx + x, cy + y, value.clone());
+        plot(grid, cx - x, cy + y, value.clone());
+        plot(grid, cx + x, cy - y, value.clone());
+        plot(grid, cx - x, cy - y, value.clone());
+    });
+}
+
+// Ellipse with its inside filled, covering exactly the cells inside draw_ellipse's outline
+pub fn fill_ellipse<T: Clone>(grid: &mut Grid<T>, center: Vector2, radii: Vector2, value: T) {
+    let (cx, cy, rx, ry) = match extent(grid, center, radii) {
+        Extent::Outside => return,
+        Extent::Covers => return grid.clear(value),
+        Extent::Cells(cx, cy, rx, ry) => (cx, cy, rx, ry),
+    };
+
+    ellipse_points(rx, ry, |x, y| {
+        fill_span(grid, cx - x, cx + x, cy + y, value.clone());
+        fill_span(grid, cx - x, cx + x, cy - y, value.clone());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{vector::vector3::Vector3, vertex::Vertex};
+
+    use super::*;
+
+    fn filled(grid: &Grid<char>) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if *grid.get(x, y).unwrap() == '#' {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn horizontal_line_test() {
+        let mut grid = Grid::new(' ', 10, 10);
+        draw_line(&mut grid, Vector2::new(1, 2), Vector2::new(4, 2), '#');
+        assert_eq!(filled(&grid), vec![(1, 2), (2, 2), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn vertical_line_test() {
+        let mut grid = Grid::new(' ', 10, 10);
+        draw_line(&mut grid, Vector2::new(3, 4), Vector2::new(3, 1), '#');
+        assert_eq!(filled(&grid), vec![(3, 1), (3, 2), (3, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn diagonal_line_test() {
+        let mut grid = Grid::new(' ', 10, 10);
+        draw_line(&mut grid, Vector2::new(3, 3), Vector2::new(0, 0), '#');
+        assert_eq!(filled(&grid), vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn steep_line_test() {
+        let mut grid = Grid::new(' ', 10, 10);
+        draw_line(&mut grid, Vector2::new(0, 0), Vector2::new(1, 4), '#');
+
+        // One cell per row, since the line is steeper than 45 degrees
+        let cells = filled(&grid);
+        assert_eq!(cells.len(), 5);
+        for (y, (x, cell_y)) in cells.into_iter().enumerate() {
+            assert_eq!(cell_y, y);
+            assert!(x <= 1);
+        }
+    }
+
+    #[test]
+    fn off_screen_line_test() {
+        let mut grid = Grid::new(' ', 5, 5);
+        draw_line(&mut grid, Vector2::new(-100, 2), Vector2::new(100, 2), '#');
+        assert_eq!(filled(&grid), vec![(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)]);
+
+        let mut grid = Grid::new(' ', 5, 5);
+        draw_line(&mut grid, Vector2::new(-10, -10), Vector2::new(-1, 20), '#');
+        assert!(filled(&grid).is_empty());
+    }
+
+    #[test]
+    fn scissored_line_test() {
+        let line = |a: Vector2, b: Vector2, scissor: Scissor| {
+            let mut grid = Grid::new(' ', 10, 10);
+            draw_scissored_line(&mut grid, a, b, '#', scissor);
+            filled(&grid)
+        };
+
+        let scissor = Scissor::new(2, 1, 3, 4);
+        let cells = line(Vector2::new(-5, 3), Vector2::new(20, 3), scissor);
+        assert_eq!(cells, vec![(2, 3), (3, 3), (4, 3)]);
+
+        // Diagonals stop at the edges of the scissor too
+        let cells = line(Vector2::new(0, 0), Vector2::new(9, 9), scissor);
+        assert_eq!(cells, vec![(2, 2), (3, 3), (4, 4)]);
+
+        // Nothing is drawn when the scissor is outside the grid
+        let outside = Scissor::new(12, 0, 5, 5);
+        assert!(line(Vector2::new(0, 2), Vector2::new(9, 2), outside).is_empty());
+    }
+
+    #[test]
+    fn wireframe_test() {
+        let a = Vector3::new(0, 0, 0);
+        let b = Vector3::new(0, 4, 0);
+        let c = Vector3::new(4, 0, 0);
+        let vertex = |pos| Vertex {
+            pos,
+            tex_coord: None,
+            normal: None,
+            w: 1.0,
+        };
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        let mut grid = Grid::new(' ', 10, 10);
+        draw_wireframe(&mut grid, &t, '#');
+
+        let cells = filled(&grid);
+        assert_eq!(cells.len(), 12);
+        assert!(cells.contains(&(0, 4)));
+        assert!(cells.contains(&(4, 0)));
+        assert!(cells.contains(&(2, 2)));
+        assert!(!cells.contains(&(1, 1)));
+    }
+
+    #[test]
+    fn fill_triangle_test() {
+        let a = Vector3::new(1, 1, 0);
+        let b = Vector3::new(1, 5, 0);
+        let c = Vector3::new(7, 1, 0);
+        let vertex = |pos| Vertex {
+            pos,
+            tex_coord: None,
+            normal: None,
+            w: 1.0,
+        };
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        let mut grid = Grid::new('.', 9, 7);
+        fill_triangle(&mut grid, &t, '#');
+        assert_eq!(
+            grid.to_string(),
+            "\
+.........
+.#######.
+.#####...
+.####....
+.##......
+.#.......
+.........
+"
+        );
+
+        // Works for any kind of cell, rows 1 and 2 are cut off at the right edge of the grid
+        let mut grid = Grid::new(false, 4, 3);
+        fill_triangle(&mut grid, &t, true);
+        let cells: Vec<_> = grid.iter().filter(|(_, _, cell)| **cell).collect();
+        assert_eq!(cells.len(), 6);
+        assert!(cells.iter().all(|(x, y, _)| *x >= 1 && *y >= 1));
+    }
+
+    #[test]
+    fn circle_test() {
+        let mut grid = Grid::new('.', 7, 7);
+        draw_circle(&mut grid, Vector2::new(3, 3), 2.0, '#');
+        assert_eq!(
+            grid.to_string(),
+            "\
+.......
+..###..
+.#...#.
+.#...#.
+.#...#.
+..###..
+.......
+"
+        );
+
+        fill_circle(&mut grid, Vector2::new(3, 3), 2.0, '#');
+        assert_eq!(
+            grid.to_string(),
+            "\
+.......
+..###..
+.#####.
+.#####.
+.#####.
+..###..
+.......
+"
+        );
+    }
+
+    #[test]
+    fn small_circle_test() {
+        // A radius of 0 is a single cell
+        let mut grid = Grid::new(' ', 3, 3);
+        draw_circle(&mut grid, Vector2::new(1, 1), 0.0, '#');
+        assert_eq!(filled(&grid), vec![(1, 1)]);
+
+        let mut grid = Grid::new(' ', 3, 3);
+        draw_circle(&mut grid, Vector2::new(1, 1), -1.0, '#');
+        fill_circle(&mut grid, Vector2::new(1, 1), -1.0, '#');
+        assert!(filled(&grid).is_empty());
+    }
+
+    #[test]
+    fn clipped_circle_test() {
+        // Entirely off the grid
+        let mut grid = Grid::new(' ', 5, 5);
+        draw_circle(&mut grid, Vector2::new(-20, -20), 3.0, '#');
+        fill_circle(&mut grid, Vector2::new(20, 2), 3.0, '#');
+        draw_ellipse(&mut grid, Vector2::new(2, -10), Vector2::new(4, 3), '#');
+        assert!(filled(&grid).is_empty());
+
+        // Only the quarter in the grid is drawn
+        draw_circle(&mut grid, Vector2::new(0, 0), 2.0, '#');
+        assert_eq!(filled(&grid), vec![(2, 0), (2, 1), (0, 2), (1, 2)]);
+
+        fill_circle(&mut grid, Vector2::new(0, 0), 2.0, '#');
+        assert_eq!(filled(&grid).len(), 8);
+    }
+
+    #[test]
+    fn huge_circle_test() {
+        // Centers and radii far past the grid finish without stepping through every cell
+        let mut grid = Grid::new(' ', 5, 5);
+        draw_circle(&mut grid, Vector2::new(1e30, 2.0), 3.0, '#');
+        fill_circle(&mut grid, Vector2::new(2.0, 2.0), f32::NAN, '#');
+        fill_ellipse(
+            &mut grid,
+            Vector2::new(2.0, f32::MAX),
+            Vector2::new(1e30, 1e30),
+            '#',
+        );
+        draw_circle(&mut grid, Vector2::new(2.0, 2.0), 1e12, '#');
+        draw_ellipse(
+            &mut grid,
+            Vector2::new(2.0, 2.0),
+            Vector2::new(f32::INFINITY, 9.0),
+            '#',
+        );
+        assert!(filled(&grid).is_empty());
+
+        // A filled one covering the grid fills every cell
+        fill_circle(&mut grid, Vector2::new(2.0, 2.0), 1e12, '#');
+        assert_eq!(filled(&grid).len(), 25);
+
+        let mut grid = Grid::new(' ', 5, 5);
+        fill_ellipse(
+            &mut grid,
+            Vector2::new(2.0, 2.0),
+            Vector2::new(1e20, 4.0),
+            '#',
+        );
+        assert_eq!(filled(&grid).len(), 25);
+
+        // An outline crossing the grid from a far center is still drawn
+        let mut grid = Grid::new(' ', 5, 5);
+        draw_circle(&mut grid, Vector2::new(-1000.0, 2.0), 1002.0, '#');
+        assert!(filled(&grid).contains(&(2, 2)));
+    }
+
+    #[test]
+    fn ellipse_test() {
+        let mut grid = Grid::new('.', 9, 5);
+        draw_ellipse(&mut grid, Vector2::new(4, 2), Vector2::new(4, 2), '#');
+        assert_eq!(
+            grid.to_string(),
+            "\
+..#####..
+.#.....#.
+#.......#
+.#.....#.
+..#####..
+"
+        );
+
+        fill_ellipse(&mut grid, Vector2::new(4, 2), Vector2::new(4, 2), '#');
+        assert_eq!(
+            grid.to_string(),
+            "\
+..#####..
+.#######.
+#########
+.#######.
+..#####..
+"
+        );
+    }
+
+    #[test]
+    fn flat_ellipse_test() {
+        // A radius of 0 is a straight line
+        let mut grid = Grid::new(' ', 7, 5);
+        draw_ellipse(&mut grid, Vector2::new(3, 2), Vector2::new(2, 0), '#');
+        assert_eq!(filled(&grid), vec![(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)]);
+
+        let mut grid = Grid::new(' ', 7, 5);
+        draw_ellipse(&mut grid, Vector2::new(3, 2), Vector2::new(0, 2), '#');
+        assert_eq!(filled(&grid), vec![(3, 0), (3, 1), (3, 2), (3, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn straight_bezier_test() {
+        // Control points on the line between the end points give the same cells as the line
+        let (a, b) = (Vector2::new(1, 2), Vector2::new(13, 7));
+        let mut line = Grid::new(' ', 15, 10);
+        draw_line(&mut line, a, b, '#');
+
+        let mut quadratic = Grid::new(' ', 15, 10);
+        draw_bezier_quadratic(&mut quadratic, a, a.lerp(b, 0.5), b, '#');
+        assert_eq!(filled(&quadratic), filled(&line));
+
+        let mut cubic = Grid::new(' ', 15, 10);
+        draw_bezier_cubic(&mut cubic, a, a.lerp(b, 0.25), a.lerp(b, 0.6), b, '#');
+        assert_eq!(filled(&cubic), filled(&line));
+    }
+
+    #[test]
+    fn bezier_end_points_test() {
+        let mut grid = Grid::new(' ', 20, 20);
+        let (start, end) = (Vector2::new(0, 19), Vector2::new(19, 19));
+        draw_bezier_quadratic(&mut grid, start, Vector2::new(10, -15), end, '#');
+
+        let cells = filled(&grid);
+        assert!(cells.contains(&(0, 19)));
+        assert!(cells.contains(&(19, 19)));
+
+        // The top of the arch is halfway to the control point
+        assert!(cells.contains(&(10, 2)));
+        assert!(cells.iter().all(|(_, y)| *y >= 2));
+
+        let mut grid = Grid::new(' ', 20, 20);
+        let (start, end) = (Vector2::new(2, 3), Vector2::new(17, 16));
+        draw_bezier_cubic(
+            &mut grid,
+            start,
+            Vector2::new(30, 0),
+            Vector2::new(-10, 20),
+            end,
+            '#',
+        );
+
+        let cells = filled(&grid);
+        assert!(cells.contains(&(2, 3)));
+        assert!(cells.contains(&(17, 16)));
+    }
+
+    #[test]
+    fn connected_bezier_test() {
+        // Every cell of the curve touches the next one, so there are no gaps between segments
+        let mut grid = Grid::new(' ', 30, 30);
+        draw_bezier_cubic(
+            &mut grid,
+            Vector2::new(1, 28),
+            Vector2::new(1, 1),
+            Vector2::new(28, 28),
+            Vector2::new(28, 1),
+            '#',
+        );
+
+        let cells = filled(&grid);
+        for (x, y) in &cells {
+            let neighbors = cells
+                .iter()
+                .filter(|(nx, ny)| {
+                    (nx, ny) != (x, y) && nx.abs_diff(*x) <= 1 && ny.abs_diff(*y) <= 1
+                })
+                .count();
+            assert!(neighbors >= 1);
+        }
+    }
+}
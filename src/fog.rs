@@ -0,0 +1,88 @@
+// Which end of the ramp distant geometry fades towards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogMode {
+    // Fades into the darkest character, like a dark background
+    Darken,
+    // Fades into the brightest character, so it also shows depth on unlit surfaces
+    Lighten,
+}
+
+// Depth-cueing: fragments fade between near and far view space distances from the camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    pub near: f32,
+    pub far: f32,
+    pub mode: FogMode,
+}
+
+impl Fog {
+    pub fn new(near: f32, far: f32, mode: FogMode) -> Fog {
+        Fog { near, far, mode }
+    }
+
+    // How foggy a point depth away from the camera is, 0 up to near and 1 from far on
+    pub fn amount(&self, depth: f32) -> f32 {
+        if self.far <= self.near {
+            return if depth < self.near { 0.0 } else { 1.0 };
+        }
+
+        ((depth - self.near) / (self.far - self.near)).clamp(0.0, 1.0)
+    }
+
+    // Moves an intensity in [0, 1] towards the end of the ramp the fog fades to
+    pub fn apply(&self, intensity: f32, depth: f32) -> f32 {
+        let amount = self.amount(depth);
+
+        match self.mode {
+            FogMode::Darken => intensity * (1.0 - amount),
+            FogMode::Lighten => intensity + (1.0 - intensity) * amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::light::{DEFAULT_RAMP, ramp_char};
+
+    use super::*;
+
+    #[test]
+    fn range_test() {
+        let fog = Fog::new(2.0, 6.0, FogMode::Darken);
+
+        // Nothing changes up to near, and everything is gone from far on
+        assert_eq!(ramp_char(fog.apply(1.0, 1.0), DEFAULT_RAMP), '@');
+        assert_eq!(ramp_char(fog.apply(1.0, 2.0), DEFAULT_RAMP), '@');
+        assert_eq!(ramp_char(fog.apply(1.0, 6.0), DEFAULT_RAMP), '.');
+        assert_eq!(ramp_char(fog.apply(1.0, 100.0), DEFAULT_RAMP), '.');
+
+        let fog = Fog::new(2.0, 6.0, FogMode::Lighten);
+        assert_eq!(ramp_char(fog.apply(0.0, 2.0), DEFAULT_RAMP), '.');
+        assert_eq!(ramp_char(fog.apply(0.0, 6.0), DEFAULT_RAMP), '@');
+    }
+
+    #[test]
+    fn interpolation_test() {
+        let darken = Fog::new(2.0, 6.0, FogMode::Darken);
+        let lighten = Fog::new(2.0, 6.0, FogMode::Lighten);
+
+        // Linear in depth between near and far
+        assert_abs_diff_eq!(darken.amount(4.0), 0.5);
+        assert_abs_diff_eq!(darken.amount(3.0), 0.25);
+        assert_abs_diff_eq!(darken.apply(0.8, 4.0), 0.4);
+        assert_abs_diff_eq!(lighten.apply(0.2, 5.0), 0.8);
+
+        // Three quarters of the way up a ramp of 12 characters
+        assert_eq!(ramp_char(darken.apply(1.0, 3.0), DEFAULT_RAMP), '*');
+    }
+
+    #[test]
+    fn empty_range_test() {
+        // Fog with no range is a hard cut at near
+        let fog = Fog::new(3.0, 3.0, FogMode::Darken);
+        assert_eq!(fog.amount(2.9), 0.0);
+        assert_eq!(fog.amount(3.0), 1.0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::mem;
+
+use crate::Grid;
+
+// Draws into the back grid while the front grid holds what is currently on screen,
+// so presenting a frame only has to write the cells that changed
+pub struct FrameBuffer<T> {
+    front: Grid<T>,
+    back: Grid<T>,
+}
+
+impl<T: Clone + PartialEq + ToString> FrameBuffer<T> {
+    // The screen is assumed to already be cleared to fill_value
+    pub fn new(fill_value: T, width: usize, height: usize) -> Self {
+        FrameBuffer {
+            front: Grid::new(fill_value.clone(), width, height),
+            back: Grid::new(fill_value, width, height),
+        }
+    }
+
+    pub fn front(&self) -> &Grid<T> {
+        &self.front
+    }
+
+    pub fn back_mut(&mut self) -> &mut Grid<T> {
+        &mut self.back
+    }
+
+    // Escape codes that update the screen from the front grid to the back grid, the
+    // cursor is only moved when the next changed cell doesn't directly follow the last one
+    pub fn present(&self) -> String {
+        let mut res = String::new();
+        let mut cursor = None;
+
+        for (x, y, value) in self.back.iter() {
+            if self.front.get(x, y) == Some(value) {
+                continue;
+            }
+
+            if cursor != Some((x, y)) {
+                res.push_str(&format!("\x1B[{};{}H", y + 1, x + 1));
+            }
+
+            res.push_str(&value.to_string());
+            cursor = Some((x + 1, y));
+        }
+
+        res
+    }
+
+    // Makes the back grid the new front, the old front becomes the next frame to draw into
+    pub fn swap(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_frame_test() {
+        let mut frame = FrameBuffer::new(' ', 4, 3);
+        assert_eq!(frame.present(), "");
+
+        frame.back_mut().set('#', 1, 1);
+        frame.swap();
+        frame.back_mut().set('#', 1, 1);
+        assert_eq!(frame.present(), "");
+    }
+
+    #[test]
+    fn single_cell_test() {
+        let mut frame = FrameBuffer::new(' ', 4, 3);
+        frame.back_mut().set('#', 2, 1);
+        assert_eq!(frame.present(), "\x1B[2;3H#");
+    }
+
+    #[test]
+    fn consecutive_cells_test() {
+        let mut frame = FrameBuffer::new(' ', 4, 3);
+        frame.back_mut().set('a', 1, 0);
+        frame.back_mut().set('b', 2, 0);
+        frame.back_mut().set('c', 0, 2);
+
+        // Cells next to each other share a single cursor move
+        assert_eq!(frame.present(), "\x1B[1;2Hab\x1B[3;1Hc");
+    }
+
+    #[test]
+    fn swap_test() {
+        let mut frame = FrameBuffer::new(' ', 2, 1);
+        frame.back_mut().set('#', 0, 0);
+        frame.swap();
+        assert_eq!(frame.front().get(0, 0), Some(&'#'));
+
+        // Erasing a cell from the previous frame is written as a change
+        frame.back_mut().clear(' ');
+        assert_eq!(frame.present(), "\x1B[1;1H ");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    Vector4,
+    matrix::{matrix::Matrix, matrix4::Matrix4},
+    model::Model,
+    ray::Plane,
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+// The volume a camera sees, as six planes with their normals pointing inwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    // Left, right, bottom, top, near and far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    // Planes of the clip volume (-w <= x, y, z <= w) taken back into the space that
+    // view_projection transforms from, world space for a camera's view projection matrix
+    pub fn new(view_projection: Matrix4) -> Frustum {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|i| view_projection.row(i));
+
+        Frustum {
+            planes: [w + x, w - x, w + y, w - y, w + z, w - z].map(plane),
+        }
+    }
+
+    // False only when the sphere is entirely outside of one of the planes, so spheres near the
+    // corners can still count as inside when they aren't
+    pub fn contains_sphere(&self, center: Vector3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(center) >= -radius)
+    }
+
+    // Same as contains_sphere for the box between the min and max corners
+    pub fn contains_box(&self, min: Vector3, max: Vector3) -> bool {
+        self.planes.iter().all(|plane| {
+            // Corner furthest along the normal, when it's outside the whole box is
+            let n = plane.normal;
+            let corner = Vector3::new(
+                if n.x >= 0.0 { max.x } else { min.x },
+                if n.y >= 0.0 { max.y } else { min.y },
+                if n.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+
+    // Cheap test with the model's bounding sphere for skipping models that can't be seen
+    pub fn contains_model(&self, model: &Model) -> bool {
+        let (center, radius) = model.bounding_sphere();
+        self.contains_sphere(center, radius)
+    }
+}
+
+// Plane of the points p with a x + b y + c z + d >= 0 on the positive side
+fn plane(coefficients: Vector4) -> Plane {
+    let normal = coefficients.xyz();
+    let length = normal.length();
+
+    Plane {
+        normal: normal / length,
+        distance: -coefficients.w / length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{camera::Camera, matrix::rotation::Angle};
+
+    use super::*;
+
+    // Camera at the origin looking down -z, seeing from 0.1 to 10 away with a 90 degree fov
+    fn frustum() -> Frustum {
+        let camera = Camera::new(Angle::Degrees(90.0), 1.0, 0.1, 10.0);
+        Frustum::new(camera.view_projection())
+    }
+
+    #[test]
+    fn planes_test() {
+        let Frustum { planes } = frustum();
+
+        // Near and far planes face each other along the view direction
+        assert_abs_diff_eq!(planes[4].normal, Vector3::new(0, 0, -1), epsilon = 1e-5);
+        assert_abs_diff_eq!(planes[4].distance, 0.1, epsilon = 1e-4);
+        assert_abs_diff_eq!(planes[5].normal, Vector3::new(0, 0, 1), epsilon = 1e-5);
+        assert_abs_diff_eq!(planes[5].distance, -10.0, epsilon = 1e-3);
+
+        // The side planes are at 45 degrees
+        let side = f32::sqrt(0.5);
+        assert_abs_diff_eq!(
+            planes[0].normal,
+            Vector3::new(side, 0, -side),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            planes[3].normal,
+            Vector3::new(0, -side, -side),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn sphere_test() {
+        let frustum = frustum();
+        assert!(frustum.contains_sphere(Vector3::new(0, 0, -5), 0.5));
+
+        // Behind the camera, past the far plane and off to the side
+        assert!(!frustum.contains_sphere(Vector3::new(0, 0, 5), 1.0));
+        assert!(!frustum.contains_sphere(Vector3::new(0, 0, -12), 1.0));
+        assert!(!frustum.contains_sphere(Vector3::new(8, 0, -5), 1.0));
+
+        // Partly inside is enough
+        assert!(frustum.contains_sphere(Vector3::new(0, 0, -10.5), 1.0));
+        assert!(frustum.contains_sphere(Vector3::new(5.5, 0, -5), 1.0));
+    }
+
+    #[test]
+    fn box_test() {
+        let frustum = frustum();
+        let cube = |center: Vector3| {
+            let half = Vector3::new(0.5, 0.5, 0.5);
+            frustum.contains_box(center - half, center + half)
+        };
+
+        assert!(cube(Vector3::new(0, 0, -3)));
+        assert!(!cube(Vector3::new(0, 0, 3)));
+        assert!(!cube(Vector3::new(0, 9, -3)));
+
+        // A box around the camera reaches into the frustum
+        assert!(frustum.contains_box(Vector3::new(-1, -1, -1), Vector3::new(1, 1, 1)));
+    }
+
+    #[test]
+    fn model_test() {
+        let model = |position: Vector3| {
+            let mut model: Model = "v -1 -1 0\nv 1 -1 0\nv 0 1 0\nf 1 2 3".parse().unwrap();
+            model.set_position(position);
+            model
+        };
+
+        let frustum = frustum();
+        assert!(frustum.contains_model(&model(Vector3::new(0, 0, -5))));
+        assert!(!frustum.contains_model(&model(Vector3::new(0, 0, 5))));
+        assert!(!frustum.contains_model(&model(Vector3::new(0, 40, -5))));
+    }
+}
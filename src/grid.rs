@@ -1,5 +1,6 @@
 use core::fmt;
 use std::clone::Clone;
+use std::ops::{Index, IndexMut};
 use std::string::ToString;
 
 pub struct Grid<T> {
@@ -9,30 +10,75 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
+    // Width by height grid with every cell set to f(x, y)
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> T) -> Self {
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                data.push(f(x, y));
+            }
+        }
+
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    // Position of (x, y) in data, without checking x it would wrap around into the next row
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(y * self.width + x)
+    }
+
     pub fn get(&self, x: usize, y: usize) -> Option<&T> {
-        let index: usize = y * self.width + x;
+        let index = self.index_of(x, y)?;
         self.data.get(index)
     }
 
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        let index = self.index_of(x, y)?;
+        self.data.get_mut(index)
+    }
+
     pub fn set(&mut self, value: T, x: usize, y: usize) -> bool {
-        let index: usize = y * self.width + x;
-        if index >= self.data.len() || y >= self.height || x >= self.width {
+        let Some(cell) = self.get_mut(x, y) else {
             return false;
-        }
+        };
 
-        self.data[index] = value;
+        *cell = value;
         true
     }
 
+    // Rows from top to bottom, each one is a slice of the contiguous backing data
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(self.width.max(1)).take(self.height)
+    }
+
+    // Every cell with its (x, y) coordinates in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.iter_rows()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, value)| (x, y, value)))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
     pub fn clear(&mut self, value: T)
     where
-        T: Copy,
+        T: Clone,
     {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.set(value, x, y);
-            }
-        }
+        self.data.iter_mut().for_each(|cell| *cell = value.clone());
     }
 }
 
@@ -44,20 +90,420 @@ impl<T: Clone> Grid<T> {
             height,
         }
     }
+
+    // Cells keep their (x, y) position, anything newly exposed is set to fill_value
+    pub fn resize(&mut self, width: usize, height: usize, fill_value: T) {
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let value = self.get(x, y).unwrap_or(&fill_value);
+                data.push(value.clone());
+            }
+        }
+
+        self.data = data;
+        self.width = width;
+        self.height = height;
+    }
+
+    // Fills the w by h rectangle with its top left corner at (x, y), clipped to the grid
+    pub fn fill_rect(&mut self, value: T, x: usize, y: usize, w: usize, h: usize) {
+        let max_x = x.saturating_add(w).min(self.width);
+        let max_y = y.saturating_add(h).min(self.height);
+
+        for cy in y..max_y {
+            for cx in x..max_x {
+                self.set(value.clone(), cx, cy);
+            }
+        }
+    }
+
+    // Sets only the outline of the rectangle, edges outside of the grid are skipped
+    pub fn draw_border(&mut self, value: T, x: usize, y: usize, w: usize, h: usize) {
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        let right = x.saturating_add(w - 1);
+        let bottom = y.saturating_add(h - 1);
+
+        self.fill_rect(value.clone(), x, y, w, 1);
+        self.fill_rect(value.clone(), x, bottom, w, 1);
+        self.fill_rect(value.clone(), x, y, 1, h);
+        self.fill_rect(value, right, y, 1, h);
+    }
+
+    // Copies src onto the grid with its top left corner at (x, y), clipped to the grid
+    pub fn blit(&mut self, src: &Grid<T>, x: usize, y: usize) {
+        self.blit_with(src, x, y, |_, value| value.clone());
+    }
+
+    // Same as blit, but every covered cell is set to f(current, src) so cells can be blended
+    // or left alone (for example when the src cell is transparent)
+    pub fn blit_with(&mut self, src: &Grid<T>, x: usize, y: usize, f: impl Fn(&T, &T) -> T) {
+        for (sx, sy, value) in src.iter() {
+            let (Some(cx), Some(cy)) = (x.checked_add(sx), y.checked_add(sy)) else {
+                continue;
+            };
+
+            if let Some(cell) = self.get_mut(cx, cy) {
+                *cell = f(cell, value);
+            }
+        }
+    }
+
+    // Mirrored left to right
+    pub fn flip_horizontal(&self) -> Grid<T> {
+        self.remap(self.width, self.height, |x, y| (self.width - 1 - x, y))
+    }
+
+    // Mirrored top to bottom
+    pub fn flip_vertical(&self) -> Grid<T> {
+        self.remap(self.width, self.height, |x, y| (x, self.height - 1 - y))
+    }
+
+    // Turned a quarter clockwise, so the width and height swap
+    pub fn rotate_90(&self) -> Grid<T> {
+        self.remap(self.height, self.width, |x, y| (y, self.height - 1 - x))
+    }
+
+    pub fn rotate_180(&self) -> Grid<T> {
+        self.remap(self.width, self.height, |x, y| {
+            (self.width - 1 - x, self.height - 1 - y)
+        })
+    }
+
+    // Turned a quarter counter-clockwise, so the width and height swap
+    pub fn rotate_270(&self) -> Grid<T> {
+        self.remap(self.height, self.width, |x, y| (self.width - 1 - y, x))
+    }
+
+    // Width by height grid where every (x, y) is a copy of the cell at source(x, y)
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Grid<T> {
+        Grid::from_fn(width, height, |x, y| self[source(x, y)].clone())
+    }
+}
+
+// Indexed by (x, y)
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("Index out of range")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("Index out of range")
+    }
 }
 
 impl<T: ToString> fmt::Display for Grid<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut res: String = String::new();
+        // A byte per cell and per newline, enough for ASCII grids to never reallocate
+        let mut res = String::with_capacity((self.width + 1) * self.height);
         for y in 0..self.height {
             for x in 0..self.width {
                 if let Some(value) = self.get(x, y) {
                     res.push_str(&value.to_string());
                 }
             }
-            res.push_str("\n");
+            res.push('\n');
         }
 
-        write!(f, "{}", res)
+        f.write_str(&res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_out_of_bounds_test() {
+        let mut grid = Grid::new(' ', 3, 2);
+        grid.set('#', 0, 1);
+
+        assert_eq!(grid.get(0, 1), Some(&'#'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(grid.get(2, 1), Some(&' '));
+    }
+
+    #[test]
+    fn get_mut_test() {
+        let mut grid = Grid::new(1.0, 3, 2);
+        if let Some(cell) = grid.get_mut(2, 1) {
+            *cell += 0.5;
+        }
+
+        assert_eq!(grid.get(2, 1), Some(&1.5));
+        assert_eq!(grid.get_mut(3, 0), None);
+        assert_eq!(grid.get_mut(0, 2), None);
+    }
+
+    #[test]
+    fn index_test() {
+        let mut grid = Grid::new(' ', 3, 2);
+        grid[(1, 1)] = '#';
+
+        assert_eq!(grid[(1, 1)], '#');
+        assert_eq!(grid.get(1, 1), Some(&'#'));
+        assert_eq!(grid[(0, 0)], ' ');
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_out_of_range_test() {
+        let grid = Grid::new(' ', 3, 2);
+        let _ = grid[(3, 0)];
+    }
+
+    #[test]
+    fn resize_larger_test() {
+        let mut grid = Grid::new('.', 2, 2);
+        grid.set('a', 0, 0);
+        grid.set('b', 1, 1);
+        grid.resize(3, 4, ' ');
+
+        assert_eq!((grid.width, grid.height), (3, 4));
+        assert_eq!(grid.to_string(), "a. \n.b \n   \n   \n");
+    }
+
+    #[test]
+    fn resize_smaller_test() {
+        let mut grid = Grid::new('.', 4, 3);
+        grid.set('a', 1, 0);
+        grid.set('b', 3, 2);
+        grid.resize(2, 1, ' ');
+
+        assert_eq!((grid.width, grid.height), (2, 1));
+        assert_eq!(grid.to_string(), ".a\n");
+        assert_eq!(grid.get(3, 2), None);
+    }
+
+    #[test]
+    fn resize_mixed_test() {
+        // Wider but shorter
+        let mut grid = Grid::new('.', 2, 3);
+        grid.set('a', 1, 1);
+        grid.resize(3, 2, ' ');
+
+        assert_eq!(grid.to_string(), ".. \n.a \n");
+    }
+
+    #[test]
+    fn iter_rows_test() {
+        let mut grid = Grid::new(0, 3, 2);
+        grid.set(1, 2, 0);
+        grid.set(2, 0, 1);
+
+        let rows: Vec<&[i32]> = grid.iter_rows().collect();
+        assert_eq!(rows, vec![&[0, 0, 1][..], &[2, 0, 0][..]]);
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut grid = Grid::new('.', 2, 2);
+        grid.set('a', 1, 0);
+
+        let cells: Vec<(usize, usize, char)> = grid.iter().map(|(x, y, ch)| (x, y, *ch)).collect();
+        assert_eq!(
+            cells,
+            vec![(0, 0, '.'), (1, 0, 'a'), (0, 1, '.'), (1, 1, '.')]
+        );
+    }
+
+    #[test]
+    fn map_test() {
+        let mut grid = Grid::new(' ', 2, 2);
+        grid.set('#', 0, 1);
+
+        let filled = grid.map(|ch| *ch != ' ');
+        assert_eq!((filled.width, filled.height), (2, 2));
+        assert_eq!(filled.get(0, 1), Some(&true));
+        assert_eq!(filled.get(1, 1), Some(&false));
+        assert_eq!(filled.get(0, 0), Some(&false));
+    }
+
+    #[test]
+    fn fill_rect_test() {
+        let mut grid = Grid::new('.', 4, 3);
+        grid.fill_rect('#', 1, 1, 2, 2);
+        assert_eq!(grid.to_string(), "....\n.##.\n.##.\n");
+
+        // Zero sized and out of bounds rectangles don't change anything
+        grid.fill_rect('x', 0, 0, 0, 3);
+        grid.fill_rect('x', 4, 0, 2, 2);
+        assert_eq!(grid.to_string(), "....\n.##.\n.##.\n");
+    }
+
+    #[test]
+    fn fill_rect_clipped_test() {
+        let mut grid = Grid::new('.', 4, 3);
+        grid.fill_rect('#', 2, 1, 10, usize::MAX);
+        assert_eq!(grid.to_string(), "....\n..##\n..##\n");
+    }
+
+    #[test]
+    fn draw_border_test() {
+        let mut grid = Grid::new('.', 5, 4);
+        grid.draw_border('#', 0, 0, 4, 4);
+        assert_eq!(grid.to_string(), "####.\n#..#.\n#..#.\n####.\n");
+
+        // Only the edges that are on the grid get drawn
+        let mut grid = Grid::new('.', 4, 3);
+        grid.draw_border('#', 1, 1, 5, 5);
+        assert_eq!(grid.to_string(), "....\n.###\n.#..\n");
+
+        grid.draw_border('x', 0, 0, 0, 0);
+        assert_eq!(grid.to_string(), "....\n.###\n.#..\n");
+    }
+
+    #[test]
+    fn clear_test() {
+        let mut grid = Grid::new(0, 3, 2);
+        grid.set(5, 1, 0);
+        grid.set(7, 2, 1);
+        grid.clear(1);
+
+        assert!(grid.iter().all(|(_, _, value)| *value == 1));
+    }
+
+    #[test]
+    fn clear_clone_test() {
+        let mut grid = Grid::new(String::from("a"), 2, 2);
+        grid.set(String::from("b"), 1, 1);
+        grid.clear(String::from("c"));
+
+        assert_eq!(grid.to_string(), "cc\ncc\n");
+    }
+
+    #[test]
+    fn display_test() {
+        let mut grid = Grid::new('.', 4, 3);
+        grid.set('█', 1, 0);
+        grid.set('@', 3, 2);
+        assert_eq!(grid.to_string(), ".█..\n....\n...@\n");
+
+        // Cells can take up more than one character
+        let mut grid = Grid::new(0, 3, 2);
+        grid.set(42, 0, 0);
+        grid.set(-7, 2, 1);
+        assert_eq!(grid.to_string(), "4200\n00-7\n");
+
+        // Every row ends in a newline, even without any cells
+        assert_eq!(Grid::new('.', 0, 2).to_string(), "\n\n");
+        assert_eq!(Grid::new('.', 2, 0).to_string(), "");
+    }
+
+    #[test]
+    fn blit_test() {
+        let mut grid = Grid::new('.', 5, 4);
+        grid.blit(&Grid::new('#', 2, 2), 1, 1);
+        assert_eq!(grid.to_string(), ".....\n.##..\n.##..\n.....\n");
+    }
+
+    #[test]
+    fn blit_clipped_test() {
+        // Only the part of src that lands on the grid is copied
+        let mut grid = Grid::new('.', 4, 3);
+        grid.blit(&letters(), 2, 2);
+        assert_eq!(grid.to_string(), "....\n....\n..ab\n");
+
+        grid.blit(&letters(), 4, 0);
+        grid.blit(&letters(), usize::MAX, usize::MAX);
+        assert_eq!(grid.to_string(), "....\n....\n..ab\n");
+    }
+
+    #[test]
+    fn blit_with_test() {
+        // Spaces are transparent and keep what was under them
+        let mut src = Grid::new(' ', 3, 3);
+        src.draw_border('#', 0, 0, 3, 3);
+        src.set('o', 1, 1);
+        src.set(' ', 1, 0);
+
+        let mut grid = letters();
+        grid.blit_with(&src, 0, 0, |current, value| match value {
+            ' ' => *current,
+            _ => *value,
+        });
+        assert_eq!(grid.to_string(), "#b#\n#o#\n");
+    }
+
+    #[test]
+    fn from_fn_test() {
+        let checkerboard = Grid::from_fn(4, 3, |x, y| if (x + y) % 2 == 0 { '#' } else { '.' });
+        assert_eq!(checkerboard.to_string(), "#.#.\n.#.#\n#.#.\n");
+
+        // Every cell knows where it is
+        let coordinates = Grid::from_fn(5, 2, |x, y| (x, y));
+        assert_eq!((coordinates.width, coordinates.height), (5, 2));
+        assert_eq!(coordinates[(0, 0)], (0, 0));
+        assert_eq!(coordinates[(3, 1)], (3, 1));
+        assert_eq!(coordinates[(4, 0)], (4, 0));
+        assert!(coordinates.iter().all(|(x, y, cell)| *cell == (x, y)));
+
+        assert_eq!(Grid::from_fn(0, 3, |_, _| 0).to_string(), "\n\n\n");
+    }
+
+    // abc
+    // def
+    fn letters() -> Grid<char> {
+        let mut grid = Grid::new(' ', 3, 2);
+        for (i, ch) in "abcdef".chars().enumerate() {
+            grid.set(ch, i % 3, i / 3);
+        }
+        grid
+    }
+
+    #[test]
+    fn flip_test() {
+        let grid = letters();
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc\n");
+
+        // Flipping twice gives back the original
+        assert_eq!(
+            grid.flip_horizontal().flip_horizontal().to_string(),
+            "abc\ndef\n"
+        );
+    }
+
+    #[test]
+    fn rotate_test() {
+        let grid = letters();
+
+        let rotated = grid.rotate_90();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(rotated.to_string(), "da\neb\nfc\n");
+
+        let rotated = grid.rotate_180();
+        assert_eq!((rotated.width, rotated.height), (3, 2));
+        assert_eq!(rotated.to_string(), "fed\ncba\n");
+
+        let rotated = grid.rotate_270();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(rotated.to_string(), "cf\nbe\nad\n");
+
+        // Four quarter turns, or one turn each way, end up where they started
+        let full = grid.rotate_90().rotate_90().rotate_90().rotate_90();
+        assert_eq!(full.to_string(), grid.to_string());
+        assert_eq!(grid.rotate_90().rotate_270().to_string(), grid.to_string());
+    }
+
+    #[test]
+    fn transform_empty_test() {
+        let grid = Grid::new('.', 0, 2);
+        let rotated = grid.rotate_90();
+        assert_eq!((rotated.width, rotated.height), (2, 0));
+        assert_eq!(grid.flip_horizontal().to_string(), "\n\n");
     }
 }
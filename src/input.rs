@@ -0,0 +1,175 @@
+use std::{io, iter::Peekable, str::Chars, time::Duration};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::terminal::RawTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    // Ctrl-C, which raw mode delivers as a key instead of a signal
+    Interrupt,
+    Char(char),
+}
+
+impl Key {
+    // None for releases and keys the renderer doesn't use
+    pub fn from_event(event: KeyEvent) -> Option<Key> {
+        if event.kind == KeyEventKind::Release {
+            return None;
+        }
+
+        match event.code {
+            KeyCode::Up => Some(Key::Up),
+            KeyCode::Down => Some(Key::Down),
+            KeyCode::Left => Some(Key::Left),
+            KeyCode::Right => Some(Key::Right),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Esc => Some(Key::Escape),
+            KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Key::Interrupt)
+            }
+            KeyCode::Char(ch) => Some(Key::Char(ch)),
+            _ => None,
+        }
+    }
+}
+
+// Reads keys without blocking, the terminal stays in raw mode until this is dropped
+pub struct Input {
+    _terminal: RawTerminal,
+}
+
+impl Input {
+    pub fn new() -> io::Result<Input> {
+        Ok(Input {
+            _terminal: RawTerminal::enter()?,
+        })
+    }
+
+    // Next pending key, or None straight away when nothing was pressed
+    pub fn poll(&mut self) -> io::Result<Option<Key>> {
+        while event::poll(Duration::ZERO)? {
+            if let Event::Key(event) = event::read()?
+                && let Some(key) = Key::from_event(event)
+            {
+                return Ok(Some(key));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// Parses the bytes a terminal sends in raw mode, unknown escape sequences and control
+// characters are skipped
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+
+    while let Some(ch) = chars.next() {
+        let key = match ch {
+            '\x1B' => parse_escape(&mut chars),
+            '\x03' => Some(Key::Interrupt),
+            '\r' | '\n' => Some(Key::Enter),
+            ch if ch.is_control() => None,
+            ch => Some(Key::Char(ch)),
+        };
+
+        keys.extend(key);
+    }
+
+    keys
+}
+
+// Arrow keys are sent as ESC [ A to ESC [ D (or ESC O A to ESC O D in application mode),
+// an escape that doesn't start a sequence is the escape key itself
+fn parse_escape(chars: &mut Peekable<Chars>) -> Option<Key> {
+    if chars.next_if(|ch| *ch == '[' || *ch == 'O').is_none() {
+        return Some(Key::Escape);
+    }
+
+    // Parameters come before the final character of the sequence
+    let last = chars.find(|ch| ('@'..='~').contains(ch))?;
+
+    match last {
+        'A' => Some(Key::Up),
+        'B' => Some(Key::Down),
+        'C' => Some(Key::Right),
+        'D' => Some(Key::Left),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_keys_test() {
+        assert_eq!(
+            parse_keys(b"\x1B[A\x1B[B\x1B[C\x1B[D"),
+            [Key::Up, Key::Down, Key::Right, Key::Left]
+        );
+
+        // Application cursor mode
+        assert_eq!(parse_keys(b"\x1BOA\x1BOD"), [Key::Up, Key::Left]);
+    }
+
+    #[test]
+    fn chars_test() {
+        assert_eq!(
+            parse_keys(b"wasd\r"),
+            [
+                Key::Char('w'),
+                Key::Char('a'),
+                Key::Char('s'),
+                Key::Char('d'),
+                Key::Enter
+            ]
+        );
+        assert_eq!(parse_keys("é".as_bytes()), [Key::Char('é')]);
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(parse_keys(b"\x1B"), [Key::Escape]);
+        assert_eq!(parse_keys(b"\x1Bw"), [Key::Escape, Key::Char('w')]);
+        assert_eq!(parse_keys(b"\x03"), [Key::Interrupt]);
+    }
+
+    #[test]
+    fn unknown_sequence_test() {
+        // Modified arrows keep their direction, other sequences like F5 are skipped
+        assert_eq!(parse_keys(b"\x1B[1;5A\x1B[15~w"), [Key::Up, Key::Char('w')]);
+
+        // Cut off in the middle of a sequence
+        assert_eq!(parse_keys(b"w\x1B["), [Key::Char('w')]);
+    }
+
+    #[test]
+    fn from_event_test() {
+        let press = |code, modifiers| Key::from_event(KeyEvent::new(code, modifiers));
+
+        assert_eq!(press(KeyCode::Up, KeyModifiers::NONE), Some(Key::Up));
+        assert_eq!(
+            press(KeyCode::Char('w'), KeyModifiers::NONE),
+            Some(Key::Char('w'))
+        );
+        assert_eq!(
+            press(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(Key::Interrupt)
+        );
+        assert_eq!(press(KeyCode::F(5), KeyModifiers::NONE), None);
+
+        let mut release = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(Key::from_event(release), None);
+    }
+}
@@ -0,0 +1,32 @@
+pub mod animation;
+pub mod braille;
+pub mod camera;
+pub mod clipping;
+pub mod color;
+pub mod draw;
+pub mod fog;
+pub mod frame_buffer;
+pub mod frustum;
+pub mod grid;
+pub mod input;
+pub mod light;
+pub mod material;
+pub mod matrix;
+pub mod model;
+pub mod rasterizer;
+pub mod ray;
+pub mod render;
+pub mod scene;
+pub mod terminal;
+pub mod texture;
+pub mod triangle;
+pub mod vector;
+pub mod vertex;
+
+pub use crate::vector::vector2::Vector2;
+pub use crate::vector::vector4::Vector4;
+pub use grid::Grid;
+
+// Viewport size used when the terminal size can't be detected
+pub const WIDTH: usize = 200;
+pub const HEIGHT: usize = 100;
@@ -0,0 +1,385 @@
+use crate::{
+    fog::Fog,
+    material::Material,
+    rasterizer::Interpolate,
+    triangle::Triangle,
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+// Characters ordered from darkest to brightest
+pub const DEFAULT_RAMP: &str = ".,-~:;=!*#$@";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    // Direction the light travels in (from the light towards the scene)
+    pub direction: Vector3,
+    pub intensity: f32,
+}
+
+impl Light {
+    pub fn new(direction: Vector3, intensity: f32) -> Light {
+        Light {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+
+    // Lambertian (n · l) intensity in [0, 1], surfaces facing away are unlit
+    pub fn lambert(&self, normal: Vector3) -> f32 {
+        let l = -self.direction;
+        let value = normal.normalize().dot(l) * self.intensity;
+        value.clamp(0.0, 1.0)
+    }
+
+    // Blinn-Phong highlight in [0, 1] seen from the view direction (from the surface towards
+    // the viewer), brightest when the normal is halfway between the light and the viewer.
+    // Surfaces facing away from the light get no highlight.
+    pub fn specular(&self, normal: Vector3, view: Vector3, shininess: f32) -> f32 {
+        let l = -self.direction;
+        let normal = normal.normalize();
+        let Some(half) = (l + view.normalize()).try_normalize() else {
+            return 0.0;
+        };
+
+        if normal.dot(l) <= 0.0 {
+            return 0.0;
+        }
+
+        let value = normal.dot(half).max(0.0).powf(shininess) * self.intensity;
+        value.clamp(0.0, 1.0)
+    }
+}
+
+// How a scene is lit and seen, the same for every triangle in it
+#[derive(Debug, Clone, Copy)]
+pub struct Lighting<'a> {
+    pub lights: &'a [Light],
+    // Direction from the scene towards the viewer
+    pub view: Vector3,
+    // World space position of the viewer. When set, highlights are seen from the direction of
+    // every fragment towards it instead of from view, which is only right for a viewer that is
+    // infinitely far away.
+    pub eye: Option<Vector3>,
+    pub fog: Option<Fog>,
+}
+
+impl<'a> Lighting<'a> {
+    // Seen from the view direction everywhere, without fog
+    pub fn new(lights: &'a [Light], view: Vector3) -> Lighting<'a> {
+        Lighting {
+            lights,
+            view,
+            eye: None,
+            fog: None,
+        }
+    }
+}
+
+// Everything the fragments of a triangle are shaded with
+#[derive(Debug, Clone, Copy)]
+pub struct Shading<'a> {
+    pub lighting: Lighting<'a>,
+    pub material: &'a Material,
+    pub ramp: &'a str,
+    // World space face normal, for flat shading when the vertices have no normals
+    pub normal: Vector3,
+    // World space positions of a, b and c, for finding the direction towards Lighting::eye
+    pub world: Option<[Vector3; 3]>,
+}
+
+// Maps an intensity in [0, 1] to a character of the ramp, an empty ramp only has spaces
+pub fn ramp_char(intensity: f32, ramp: &str) -> char {
+    let ramp: Vec<char> = ramp.chars().collect();
+    let Some(last) = ramp.len().checked_sub(1) else {
+        return ' ';
+    };
+
+    let intensity = intensity.clamp(0.0, 1.0);
+    let index = f32::round(intensity * (last as f32)) as usize;
+    ramp[index]
+}
+
+// Brightness in [0, 1] of a point of a triangle given its screen space barycentric weights
+// (for a, b and c), lighting its material with every light.
+// Uses the perspective-correct interpolated vertex normals (renormalized by lambert for every
+// fragment), falling back to the shading's face normal (flat shading) when the vertices have
+// no normals. The view direction points from the fragment to the eye when both the eye and the
+// world positions are known.
+// Fog is applied with the view space depth of the point, 1 / w is affine in screen space so
+// it's interpolated instead of w.
+pub fn intensity(t: &Triangle, weights: Vector3, shading: &Shading) -> f32 {
+    let perspective = t.perspective_weights(weights);
+    let normal = match (t.a.normal, t.b.normal, t.c.normal) {
+        (Some(a), Some(b), Some(c)) => Vector3::barycentric_lerp(*a, *b, *c, perspective),
+        _ => shading.normal,
+    };
+
+    let Lighting {
+        lights,
+        view,
+        eye,
+        fog,
+    } = shading.lighting;
+
+    let view = match (eye, shading.world) {
+        (Some(eye), Some([a, b, c])) => {
+            let position = Vector3::barycentric_lerp(a, b, c, perspective);
+            (eye - position).try_normalize().unwrap_or(view)
+        }
+        _ => view,
+    };
+
+    let mut intensity = shading.material.brightness(normal, view, lights);
+
+    if let Some(fog) = fog {
+        let inverse_w = f32::barycentric_lerp(1.0 / t.a.w, 1.0 / t.b.w, 1.0 / t.c.w, weights);
+        intensity = fog.apply(intensity, 1.0 / inverse_w);
+    }
+
+    intensity
+}
+
+// Character of the ramp for the intensity of a point of a triangle
+pub fn shade(t: &Triangle, weights: Vector3, shading: &Shading) -> char {
+    ramp_char(intensity(t, weights, shading), shading.ramp)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::vertex::Vertex;
+
+    use super::*;
+
+    #[test]
+    fn ramp_char_test() {
+        let ramp = " .:-=+*#%@";
+        assert_eq!(ramp_char(0.0, ramp), ' ');
+        assert_eq!(ramp_char(1.0, ramp), '@');
+        assert_eq!(ramp_char(0.5, ramp), '+');
+
+        // Out of range intensities are clamped
+        assert_eq!(ramp_char(-1.0, ramp), ' ');
+        assert_eq!(ramp_char(2.0, ramp), '@');
+
+        // Nothing to pick from
+        assert_eq!(ramp_char(0.5, ""), ' ');
+        assert_eq!(ramp_char(1.0, "#"), '#');
+    }
+
+    #[test]
+    fn lambert_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        // Facing the light is brightest
+        assert_abs_diff_eq!(light.lambert(Vector3::new(0, 0, 1)), 1.0);
+
+        // Perpendicular and facing away are unlit
+        assert_abs_diff_eq!(light.lambert(Vector3::new(1, 0, 0)), 0.0);
+        assert_abs_diff_eq!(light.lambert(Vector3::new(0, 0, -1)), 0.0);
+
+        let angled = light.lambert(Vector3::new(1, 0, 1));
+        assert!(angled > 0.0 && angled < 1.0);
+    }
+
+    #[test]
+    fn specular_test() {
+        let light = Light::new(Vector3::new(0, -1, -1), 1.0);
+        let normal = Vector3::new(0, 0, 1);
+
+        // The viewer is where the light is reflected to, so the normal is the half vector
+        let reflected = Vector3::new(0, -1, 1);
+        assert_abs_diff_eq!(light.specular(normal, reflected, 16.0), 1.0, epsilon = 1e-5);
+
+        // Moving the viewer away from the reflection makes the highlight drop off, faster for
+        // shinier surfaces
+        let offset = Vector3::new(0, 0, 1);
+        let dull = light.specular(normal, offset, 4.0);
+        let shiny = light.specular(normal, offset, 64.0);
+        assert!(dull < 1.0);
+        assert!(shiny < dull);
+
+        // No highlight without light reaching the surface
+        assert_eq!(light.specular(-normal, Vector3::new(0, 1, -1), 16.0), 0.0);
+    }
+
+    #[test]
+    fn shade_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        let ramp = " .:-=+*#%@";
+
+        let a = Vector3::new(0, 0, 0);
+        let b = Vector3::new(1, 0, 0);
+        let c = Vector3::new(0, 1, 0);
+        let facing = Vector3::new(0, 0, 1);
+        let away = Vector3::new(0, 0, -1);
+        let weights = Vector3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0);
+
+        let material = Material::new("default");
+        let shaded = |t: &Triangle, lights: &[Light], material: &Material| {
+            let shading = Shading {
+                lighting: Lighting::new(lights, Vector3::new(0, 0, 1)),
+                material,
+                ramp,
+                normal: facing,
+                world: None,
+            };
+            shade(t, weights, &shading)
+        };
+
+        let vertex = |pos, normal| Vertex {
+            pos,
+            tex_coord: None,
+            normal,
+            w: 1.0,
+        };
+
+        let lit = Triangle {
+            a: vertex(&a, Some(&facing)),
+            b: vertex(&b, Some(&facing)),
+            c: vertex(&c, Some(&facing)),
+        };
+        assert_eq!(shaded(&lit, &[light], &material), '@');
+
+        let unlit = Triangle {
+            a: vertex(&a, Some(&away)),
+            b: vertex(&b, Some(&away)),
+            c: vertex(&c, Some(&away)),
+        };
+        assert_eq!(shaded(&unlit, &[light], &material), ' ');
+
+        // Without vertex normals the shading's face normal is used
+        let flat = Triangle {
+            a: vertex(&a, None),
+            b: vertex(&b, None),
+            c: vertex(&c, None),
+        };
+        assert_eq!(shaded(&flat, &[light], &material), '@');
+
+        let lights = [light];
+        let turned_away = Shading {
+            lighting: Lighting::new(&lights, Vector3::new(0, 0, 1)),
+            material: &material,
+            ramp,
+            normal: away,
+            world: None,
+        };
+        assert_eq!(shade(&flat, weights, &turned_away), ' ');
+
+        // Lights add up, and no lights leaves everything unlit
+        let dim = Light::new(Vector3::new(0, 0, -1), 0.5);
+        assert_eq!(shaded(&lit, &[dim], &material), ramp_char(0.5, ramp));
+        assert_eq!(shaded(&lit, &[dim, dim], &material), '@');
+        assert_eq!(shaded(&lit, &[], &material), ' ');
+
+        // Ambient light still reaches surfaces facing away from every light
+        let ambient = Material {
+            ambient: 0.5,
+            ..Material::new("ambient")
+        };
+        assert_eq!(shaded(&unlit, &[light], &ambient), ramp_char(0.5, ramp));
+        assert_eq!(shaded(&lit, &[light], &ambient), '@');
+    }
+
+    #[test]
+    fn perspective_normal_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        let material = Material::new("default");
+        let lights = [light];
+        let shading = Shading {
+            lighting: Lighting::new(&lights, Vector3::new(0, 0, 1)),
+            material: &material,
+            ramp: DEFAULT_RAMP,
+            normal: Vector3::new(0, 0, 1),
+            world: None,
+        };
+
+        let a = Vector3::new(0, 0, 0);
+        let b = Vector3::new(1, 0, 0);
+        let c = Vector3::new(0, 1, 0);
+        let facing = Vector3::new(0, 0, 1);
+        let side = Vector3::new(1, 0, 0);
+
+        // b and c are three times further away than a, so the middle of the screen is mostly a
+        let t = Triangle {
+            a: Vertex {
+                pos: &a,
+                tex_coord: None,
+                normal: Some(&facing),
+                w: 1.0,
+            },
+            b: Vertex {
+                pos: &b,
+                tex_coord: None,
+                normal: Some(&side),
+                w: 3.0,
+            },
+            c: Vertex {
+                pos: &c,
+                tex_coord: None,
+                normal: Some(&side),
+                w: 3.0,
+            },
+        };
+
+        // Weights of (0.6, 0.2, 0.2) instead of thirds give a normal of (0.4, 0, 0.6)
+        let weights = Vector3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0);
+        let expected = Vector3::new(0.4, 0, 0.6).normalize().z;
+        assert_abs_diff_eq!(intensity(&t, weights, &shading), expected, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn eye_test() {
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let material = Material {
+            diffuse: 0.0,
+            specular: 1.0,
+            shininess: 64.0,
+            ..Material::new("shiny")
+        };
+
+        // A floor lit from straight above, with the eye right above a
+        let a = Vector3::new(0, 0, 0);
+        let b = Vector3::new(4, 0, 0);
+        let c = Vector3::new(0, 4, 0);
+        let up = Vector3::new(0, 0, 1);
+        let vertex = |pos| Vertex {
+            pos,
+            tex_coord: None,
+            normal: Some(&up),
+            w: 1.0,
+        };
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        let seen_from = |eye| {
+            let shading = Shading {
+                lighting: Lighting {
+                    eye,
+                    ..Lighting::new(&lights, up)
+                },
+                material: &material,
+                ramp: DEFAULT_RAMP,
+                normal: up,
+                world: Some([a, b, c]),
+            };
+            let at_a = intensity(&t, Vector3::new(1, 0, 0), &shading);
+            let at_b = intensity(&t, Vector3::new(0, 1, 0), &shading);
+            (at_a, at_b)
+        };
+
+        // Seen from infinitely far above the highlight covers the whole floor
+        let (at_a, at_b) = seen_from(None);
+        assert_abs_diff_eq!(at_a, 1.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(at_b, 1.0, epsilon = 1e-5);
+
+        // Seen from just above a, b is off to the side of the highlight
+        let (at_a, at_b) = seen_from(Some(Vector3::new(0, 0, 1)));
+        assert_abs_diff_eq!(at_a, 1.0, epsilon = 1e-5);
+        assert!(at_b < 0.1);
+    }
+}
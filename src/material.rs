@@ -0,0 +1,248 @@
+use std::{collections::HashMap, fs};
+
+use crate::{
+    light::Light,
+    model::{Model, ModelError},
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    // Kd
+    pub diffuse_color: Vector3,
+    // Light the surface gets even when no light reaches it
+    pub ambient: f32,
+    // How much of the light that reaches the surface it reflects
+    pub diffuse: f32,
+    // Strength of the highlights, brightest channel of Ks
+    pub specular: f32,
+    // Ns, higher values give smaller and sharper highlights
+    pub shininess: f32,
+}
+
+impl Material {
+    pub fn new(name: &str) -> Material {
+        Material {
+            name: String::from(name),
+            diffuse_color: Vector3::new(1.0, 1.0, 1.0),
+            ambient: 0.0,
+            diffuse: 1.0,
+            specular: 0.0,
+            shininess: 32.0,
+        }
+    }
+
+    // Color of the surface with the given normal seen from the view direction: the diffuse
+    // color scaled by ambient + diffuse * (n · l) for every light, plus white highlights, with
+    // each channel clamped to [0, 1]
+    pub fn lighting(&self, normal: Vector3, view: Vector3, lights: &[Light]) -> Vector3 {
+        let lambert: f32 = lights.iter().map(|light| light.lambert(normal)).sum();
+        let intensity = (self.ambient + self.diffuse * lambert).clamp(0.0, 1.0);
+
+        let highlight: f32 = lights
+            .iter()
+            .map(|light| light.specular(normal, view, self.shininess))
+            .sum();
+
+        let color =
+            self.diffuse_color * intensity + Vector3::new(1, 1, 1) * (self.specular * highlight);
+        color
+            .max(Vector3::new(0.0, 0.0, 0.0))
+            .min(Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    // Brightness of the lit color, its brightest channel
+    pub fn brightness(&self, normal: Vector3, view: Vector3, lights: &[Light]) -> f32 {
+        let color = self.lighting(normal, view, lights);
+        color.x.max(color.y).max(color.z)
+    }
+
+    // Reads every material of a .mtl file, keyed by name
+    pub fn load_library(path: &str) -> Result<HashMap<String, Material>, ModelError> {
+        let data = fs::read_to_string(path)?;
+
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        let mut current: Option<Material> = None;
+
+        for (index, content) in data.lines().enumerate() {
+            let line_number = index + 1;
+            let line = content.split_whitespace().collect::<Vec<&str>>();
+
+            let Some((command, parameters)) = line.split_first() else {
+                continue;
+            };
+
+            match *command {
+                "newmtl" => {
+                    if let Some(material) = current.take() {
+                        materials.insert(material.name.clone(), material);
+                    }
+
+                    current = parameters.first().map(|name| Material::new(name));
+                }
+                "Kd" => {
+                    let color = Model::to_floats(parameters, 3, line_number, content)?;
+                    if let Some(material) = current.as_mut() {
+                        material.diffuse_color = Vector3::new(color[0], color[1], color[2]);
+                    }
+                }
+                "Ks" => {
+                    let color = Model::to_floats(parameters, 3, line_number, content)?;
+                    if let Some(material) = current.as_mut() {
+                        material.specular = color[0].max(color[1]).max(color[2]);
+                    }
+                }
+                "Ns" => {
+                    let shininess = Model::to_floats(parameters, 1, line_number, content)?;
+                    if let Some(material) = current.as_mut() {
+                        material.shininess = shininess[0];
+                    }
+                }
+                _ => continue,
+            }
+        }
+
+        if let Some(material) = current {
+            materials.insert(material.name.clone(), material);
+        }
+
+        Ok(materials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    // Looking down -z
+    const VIEW: Vector3 = Vector3 {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+
+    fn material() -> Material {
+        Material {
+            diffuse_color: Vector3::new(1.0, 0.5, 0.0),
+            ambient: 0.2,
+            diffuse: 0.6,
+            ..Material::new("test")
+        }
+    }
+
+    #[test]
+    fn ambient_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        // Facing away from the light only leaves the ambient term
+        let color = material().lighting(Vector3::new(0, 0, -1), VIEW, &[light]);
+        assert_abs_diff_eq!(color.x, 0.2);
+        assert_abs_diff_eq!(color.y, 0.1);
+        assert_abs_diff_eq!(color.z, 0.0);
+
+        // Same without any lights
+        let unlit = material().lighting(Vector3::new(0, 0, 1), VIEW, &[]);
+        assert_abs_diff_eq!(unlit.x, 0.2);
+    }
+
+    #[test]
+    fn diffuse_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        // Facing the light gets ambient and the full diffuse term
+        let color = material().lighting(Vector3::new(0, 0, 1), VIEW, &[light]);
+        assert_abs_diff_eq!(color.x, 0.8);
+        assert_abs_diff_eq!(color.y, 0.4);
+        assert_abs_diff_eq!(color.z, 0.0);
+        assert_abs_diff_eq!(
+            material().brightness(Vector3::new(0, 0, 1), VIEW, &[light]),
+            0.8
+        );
+
+        // Too much light is clamped
+        let bright = Material {
+            ambient: 0.5,
+            diffuse: 2.0,
+            ..material()
+        };
+        let color = bright.lighting(Vector3::new(0, 0, 1), VIEW, &[light, light]);
+        assert_abs_diff_eq!(color.x, 1.0);
+        assert_abs_diff_eq!(color.y, 0.5);
+    }
+
+    #[test]
+    fn default_test() {
+        // The default material is plain Lambert shading
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        let normal = Vector3::new(1, 0, 1);
+        let material = Material::new("default");
+        assert_abs_diff_eq!(
+            material.brightness(normal, VIEW, &[light]),
+            light.lambert(normal)
+        );
+    }
+
+    #[test]
+    fn specular_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        let shiny = Material {
+            specular: 0.5,
+            shininess: 8.0,
+            ..material()
+        };
+
+        // The viewer sees the light reflected head on, so the highlight is added in full
+        let color = shiny.lighting(Vector3::new(0, 0, 1), VIEW, &[light]);
+        assert_abs_diff_eq!(color.x, 1.0);
+        assert_abs_diff_eq!(color.y, 0.9);
+        assert_abs_diff_eq!(color.z, 0.5);
+
+        // Looking from the side only leaves the ambient and diffuse terms
+        let side = shiny.lighting(Vector3::new(0, 0, 1), Vector3::new(1, 0, 0), &[light]);
+        assert!(side.z < 0.1);
+        assert_abs_diff_eq!(side.y, 0.4, epsilon = 0.05);
+    }
+
+    #[test]
+    fn load_specular_test() {
+        let path = env::temp_dir().join("renderer_specular_test.mtl");
+        fs::write(
+            &path,
+            "newmtl Shiny\nKs 0.2 0.8 0.4\nNs 96\n\nnewmtl Matte\n",
+        )
+        .unwrap();
+        let materials = Material::load_library(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_abs_diff_eq!(materials["Shiny"].specular, 0.8);
+        assert_abs_diff_eq!(materials["Shiny"].shininess, 96.0);
+        assert_abs_diff_eq!(materials["Matte"].specular, 0.0);
+    }
+
+    #[test]
+    fn load_errors_test() {
+        let result = Material::load_library("does/not/exist.mtl");
+        assert!(matches!(result, Err(ModelError::Io(_))));
+
+        let path = env::temp_dir().join("renderer_load_errors_test.mtl");
+        fs::write(&path, "newmtl Red\nKd 1 0\n").unwrap();
+        let result = Material::load_library(path.to_str().unwrap());
+        assert!(matches!(
+            result,
+            Err(ModelError::MalformedLine { line_number: 2, ref content }) if content == "Kd 1 0"
+        ));
+
+        fs::write(&path, "newmtl Blue\nKd 0 0 blue\n").unwrap();
+        let result = Material::load_library(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(ModelError::ParseFloat { line_number: 2, .. })
+        ));
+    }
+}
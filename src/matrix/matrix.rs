@@ -1,7 +1,105 @@
+use std::fmt;
+
+use approx::AbsDiffEq;
+
 pub trait Matrix {
     type Vector;
     fn row(&self, index: usize) -> Self::Vector;
     fn col(&self, index: usize) -> Self::Vector;
+    // Panics on an out of range index just like indexing does
+    fn col_mut(&mut self, index: usize) -> &mut Self::Vector;
     fn transpose(&self) -> Self;
     fn identity() -> Self;
+    // v's components on the diagonal and zeros everywhere else
+    fn from_diagonal(v: Self::Vector) -> Self;
+    // Sum of the diagonal
+    fn trace(&self) -> f32;
+
+    // Solves self * x = b for x, None when the matrix is singular
+    fn solve(&self, b: Self::Vector) -> Option<Self::Vector>;
+
+    // Entry-wise comparison with an absolute tolerance
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool
+    where
+        Self: AbsDiffEq<Epsilon = f32>,
+    {
+        self.abs_diff_eq(other, epsilon)
+    }
+
+    // Whether the matrix equals its transpose, entry-wise within epsilon
+    fn is_symmetric(&self, epsilon: f32) -> bool
+    where
+        Self: AbsDiffEq<Epsilon = f32> + Sized,
+    {
+        self.approx_eq(&self.transpose(), epsilon)
+    }
+}
+
+// Writes one row per line with the entries right aligned into columns
+pub(crate) fn write_rows<const N: usize>(
+    f: &mut fmt::Formatter<'_>,
+    rows: [[f32; N]; N],
+) -> fmt::Result {
+    let entries: Vec<String> = rows
+        .iter()
+        .flatten()
+        .map(|entry| match f.precision() {
+            Some(precision) => format!("{entry:.precision$}"),
+            None => entry.to_string(),
+        })
+        .collect();
+    let width = entries.iter().map(String::len).max().unwrap_or(0);
+
+    for (i, row) in entries.chunks(N).enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+
+        let row: Vec<String> = row.iter().map(|entry| format!("{entry:>width$}")).collect();
+        write!(f, "[{}]", row.join(", "))?;
+    }
+
+    Ok(())
+}
+
+// Gaussian elimination with partial pivoting on a row-major system, which stays stable
+// without forming the inverse. Pivots that are tiny next to the largest entry count as zero.
+pub(crate) fn solve_rows<const N: usize>(
+    mut rows: [[f32; N]; N],
+    mut b: [f32; N],
+) -> Option<[f32; N]> {
+    let largest = rows
+        .iter()
+        .flatten()
+        .fold(0.0, |max: f32, entry| max.max(entry.abs()));
+    let tolerance = largest * N as f32 * f32::EPSILON;
+
+    for col in 0..N {
+        // Swap the row with the largest entry in this column into place
+        let pivot = (col..N).max_by(|&i, &j| rows[i][col].abs().total_cmp(&rows[j][col].abs()))?;
+        if rows[pivot][col].abs() <= tolerance {
+            return None;
+        }
+        rows.swap(col, pivot);
+        b.swap(col, pivot);
+
+        // Eliminate the column from every row below
+        let pivot_row = rows[col];
+        for row in col + 1..N {
+            let factor = rows[row][col] / pivot_row[col];
+            for (entry, pivot) in rows[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *entry -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    // Back substitution
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let sum: f32 = (row + 1..N).map(|k| rows[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / rows[row][row];
+    }
+
+    Some(x)
 }
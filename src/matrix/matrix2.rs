@@ -1,14 +1,20 @@
-use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub},
+};
 
 use approx::{AbsDiffEq, abs_diff_eq};
 
 use crate::{
     Vector2,
-    matrix::{matrix::Matrix, scale::Scale},
-    vector::vector3::Vector3,
+    matrix::{
+        matrix::{Matrix, solve_rows, write_rows},
+        scale::Scale,
+    },
 };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix2 {
     pub x: Vector2,
     pub y: Vector2,
@@ -31,6 +37,25 @@ impl Matrix2 {
     pub fn from_cols(x: Vector2, y: Vector2) -> Self {
         Self { x, y }
     }
+
+    // Column-major, every 2 entries are one column in the same order as the x, y fields
+    pub fn from_cols_array(entries: [f32; 4]) -> Matrix2 {
+        let col = |i: usize| Vector2::from_array(array::from_fn(|j| entries[2 * i + j]));
+        Matrix2::from_cols(col(0), col(1))
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 4] {
+        array::from_fn(|i| self[i / 2][i % 2])
+    }
+
+    // Row-major, every 2 entries are one row like the arguments of new
+    pub fn from_rows_array(entries: [f32; 4]) -> Matrix2 {
+        Matrix2::from_cols_array(entries).transpose()
+    }
+
+    pub fn to_rows_array(&self) -> [f32; 4] {
+        self.transpose().to_cols_array()
+    }
 }
 
 impl Matrix for Matrix2 {
@@ -52,6 +77,10 @@ impl Matrix for Matrix2 {
         }
     }
 
+    fn col_mut(&mut self, index: usize) -> &mut Vector2 {
+        &mut self[index]
+    }
+
     fn transpose(&self) -> Matrix2 {
         Matrix2::from_rows(self.x, self.y)
     }
@@ -61,6 +90,34 @@ impl Matrix for Matrix2 {
         let y = Vector2::new(0, 1);
         Matrix2::from_cols(x, y)
     }
+
+    fn from_diagonal(v: Vector2) -> Self {
+        let x = Vector2::new(v.x, 0);
+        let y = Vector2::new(0, v.y);
+        Matrix2::from_cols(x, y)
+    }
+
+    fn trace(&self) -> f32 {
+        self.x.x + self.y.y
+    }
+
+    fn solve(&self, b: Vector2) -> Option<Vector2> {
+        let rows = array::from_fn(|i| self.row(i).to_array());
+        solve_rows(rows, b.to_array()).map(Vector2::from_array)
+    }
+}
+
+// Identity matrix by default
+impl Default for Matrix2 {
+    fn default() -> Self {
+        Matrix2::identity()
+    }
+}
+
+impl fmt::Display for Matrix2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_rows(f, array::from_fn(|i| self.row(i).to_array()))
+    }
 }
 
 // Scalar matrix
@@ -218,6 +275,16 @@ impl Index<usize> for Matrix2 {
     }
 }
 
+impl IndexMut<usize> for Matrix2 {
+    fn index_mut(&mut self, index: usize) -> &mut Vector2 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Matrix2
 where
@@ -239,9 +306,17 @@ where
     }
 }
 
+#[cfg(test)]
 mod tests {
+    use approx::assert_abs_diff_eq;
+
     use super::*;
 
+    #[test]
+    fn default_test() {
+        assert_eq!(Matrix2::default(), Matrix2::identity());
+    }
+
     #[test]
     fn indexing_test() {
         let x = Vector2::new(10, 5);
@@ -328,4 +403,97 @@ mod tests {
         assert_eq!(a - b, res);
         assert_eq!(b - a, -res);
     }
+
+    #[test]
+    fn index_mut_test() {
+        let mut m = Matrix2::identity();
+        m[1] = Vector2::new(1, 2);
+        m[0][1] = 5.0;
+        assert_eq!(m.col(1), Vector2::new(1, 2));
+        assert_eq!(m[0][1], 5.0);
+        assert_eq!(m.row(1)[0], 5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut m = Matrix2::identity();
+        m[2] = Vector2::new(1, 2);
+    }
+
+    #[test]
+    fn col_mut_test() {
+        let mut m = Matrix2::identity();
+        *m.col_mut(1) = Vector2::new(3, 4);
+        m.col_mut(0)[1] = 2.0;
+
+        assert_eq!(m.col(1), Vector2::new(3, 4));
+        assert_eq!(m.col(0)[1], 2.0);
+        assert_eq!(m.row(0), Vector2::new(1, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn col_mut_out_of_range_test() {
+        let mut m = Matrix2::identity();
+        *m.col_mut(2) = Vector2::new(1, 2);
+    }
+
+    #[test]
+    fn array_test() {
+        let m = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+        let cols = [1.0, 3.0, 2.0, 4.0];
+        let rows = [1.0, 2.0, 3.0, 4.0];
+
+        assert_eq!(m.to_cols_array(), cols);
+        assert_eq!(m.to_rows_array(), rows);
+        assert_eq!(Matrix2::from_cols_array(cols), m);
+        assert_eq!(Matrix2::from_rows_array(rows), m);
+
+        // Each layout is the other layout of the transpose
+        assert_eq!(m.transpose().to_cols_array(), rows);
+        assert_eq!(Matrix2::from_cols_array(rows), m.transpose());
+    }
+
+    #[test]
+    fn solve_test() {
+        let a = Matrix2::new(3.0, 2.0, 1.0, 2.0);
+        let x = a.solve(Vector2::new(7, 5)).unwrap();
+        assert_abs_diff_eq!(x, Vector2::new(1, 2), epsilon = 1e-6);
+
+        // The second row is twice the first
+        assert_eq!(
+            Matrix2::new(1.0, 2.0, 2.0, 4.0).solve(Vector2::new(1, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn from_diagonal_test() {
+        assert_eq!(
+            Matrix2::from_diagonal(Vector2::new(1, 1)),
+            Matrix2::identity()
+        );
+
+        let m = Matrix2::from_diagonal(Vector2::new(2, -0.5));
+        assert_eq!(m, Matrix2::new(2.0, 0.0, 0.0, -0.5));
+        assert_eq!(m * Vector2::new(3, 4), Vector2::new(6, -2));
+    }
+
+    #[test]
+    fn trace_test() {
+        assert_eq!(Matrix2::new(1.5, 2.0, -3.0, 4.0).trace(), 5.5);
+        assert_eq!(Matrix2::identity().trace(), 2.0);
+    }
+
+    #[test]
+    fn symmetric_test() {
+        assert!(Matrix2::new(1.0, 2.0, 2.0, 3.0).is_symmetric(0.0));
+        assert!(!Matrix2::new(1.0, 2.0, -2.0, 3.0).is_symmetric(1e-6));
+
+        // Within the tolerance
+        let m = Matrix2::new(1.0, 2.0, 2.0 + 1e-5, 3.0);
+        assert!(!m.is_symmetric(0.0));
+        assert!(m.is_symmetric(1e-4));
+    }
 }
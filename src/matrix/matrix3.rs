@@ -1,18 +1,23 @@
-use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub},
+};
 
 use approx::{AbsDiffEq, abs_diff_eq};
 
 use crate::{
     matrix::{
-        matrix::Matrix,
+        matrix::{Matrix, solve_rows, write_rows},
         matrix4::Matrix4,
         rotation::{Angle, Rotation},
         scale::Scale,
+        shear::Shear,
     },
-    vector::vector3::Vector3,
+    vector::{vector::Vector, vector3::Vector3},
 };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix3 {
     pub x: Vector3,
     pub y: Vector3,
@@ -20,6 +25,7 @@ pub struct Matrix3 {
 }
 
 impl Matrix3 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m00: f32,
         m01: f32,
@@ -49,6 +55,25 @@ impl Matrix3 {
         Self { x, y, z }
     }
 
+    // Column-major, every 3 entries are one column in the same order as the x, y, z fields
+    pub fn from_cols_array(entries: [f32; 9]) -> Matrix3 {
+        let col = |i: usize| Vector3::from_array(array::from_fn(|j| entries[3 * i + j]));
+        Matrix3::from_cols(col(0), col(1), col(2))
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 9] {
+        array::from_fn(|i| self[i / 3][i % 3])
+    }
+
+    // Row-major, every 3 entries are one row like the arguments of new
+    pub fn from_rows_array(entries: [f32; 9]) -> Matrix3 {
+        Matrix3::from_cols_array(entries).transpose()
+    }
+
+    pub fn to_rows_array(&self) -> [f32; 9] {
+        self.transpose().to_cols_array()
+    }
+
     pub fn to_cartesian(mat: Matrix4) -> Matrix3 {
         Matrix3::from_cols(mat.x.xyz(), mat.y.xyz(), mat.z.xyz())
     }
@@ -56,6 +81,49 @@ impl Matrix3 {
     pub fn homogenous(&self) -> Matrix4 {
         Matrix4::to_homogenous(*self)
     }
+
+    // Scales each axis independently
+    pub fn nonuniform(sx: f32, sy: f32, sz: f32) -> Matrix3 {
+        let x = Vector3::new(sx, 0, 0);
+        let y = Vector3::new(0, sy, 0);
+        let z = Vector3::new(0, 0, sz);
+        Matrix3::from_cols(x, y, z)
+    }
+
+    // Yaw, pitch and roll in radians that Matrix3::rotation turns back into this rotation
+    // matrix, with the pitch in [-90, 90] degrees and the others in [-180, 180].
+    // At a pitch of ±90 degrees (gimbal lock) the yaw and roll turn about the same axis and
+    // only their sum (or difference) is known, so the roll is taken to be 0.
+    pub fn to_euler(&self) -> (Angle, Angle, Angle) {
+        // rotation is Rz(roll) * Ry(pitch) * Rx(yaw), so the bottom left entry is -sin(pitch)
+        let [r0, r1, r2] = [self.row(0), self.row(1), self.row(2)];
+        let pitch = (-r2.x).clamp(-1.0, 1.0).asin();
+
+        let (yaw, roll) = if r2.x.abs() < 1.0 - 1e-6 {
+            (f32::atan2(r2.y, r2.z), f32::atan2(r1.x, r0.x))
+        } else {
+            // The top row is (0, sin(pitch) sin(yaw), sin(pitch) cos(yaw)) when roll is 0
+            let sign = -r2.x.signum();
+            (f32::atan2(sign * r0.y, sign * r0.z), 0.0)
+        };
+
+        (
+            Angle::Radians(yaw),
+            Angle::Radians(pitch),
+            Angle::Radians(roll),
+        )
+    }
+
+    // Nearest rotation-like matrix to this one, for stopping drift after accumulating many
+    // rotations. Gram-Schmidt on the columns: x keeps its direction, y loses its x component and
+    // z is rebuilt from the other two. Assumes the matrix is already close to a rotation, a
+    // degenerate or heavily sheared one gives meaningless axes.
+    pub fn orthonormalize(&self) -> Matrix3 {
+        let x = self.x.normalize();
+        let y = (self.y - x * x.dot(self.y)).normalize();
+        let z = x.cross(y);
+        Matrix3::from_cols(x, y, z)
+    }
 }
 
 impl Matrix for Matrix3 {
@@ -79,6 +147,10 @@ impl Matrix for Matrix3 {
         }
     }
 
+    fn col_mut(&mut self, index: usize) -> &mut Vector3 {
+        &mut self[index]
+    }
+
     fn transpose(&self) -> Matrix3 {
         Matrix3::from_rows(self.x, self.y, self.z)
     }
@@ -89,7 +161,34 @@ impl Matrix for Matrix3 {
         let z = Vector3::new(0, 0, 1);
         Matrix3::from_cols(x, y, z)
     }
+
+    fn from_diagonal(v: Vector3) -> Self {
+        Matrix3::nonuniform(v.x, v.y, v.z)
+    }
+
+    fn trace(&self) -> f32 {
+        self.x.x + self.y.y + self.z.z
+    }
+
+    fn solve(&self, b: Vector3) -> Option<Vector3> {
+        let rows = array::from_fn(|i| self.row(i).to_array());
+        solve_rows(rows, b.to_array()).map(Vector3::from_array)
+    }
+}
+
+// Identity matrix by default
+impl Default for Matrix3 {
+    fn default() -> Self {
+        Matrix3::identity()
+    }
 }
+
+impl fmt::Display for Matrix3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_rows(f, array::from_fn(|i| self.row(i).to_array()))
+    }
+}
+
 impl Scale for Matrix3 {
     type Output = Matrix3;
 
@@ -102,10 +201,7 @@ impl Rotation for Matrix3 {
     type Output = Matrix3;
 
     fn x_rotation(angle: Angle) -> Matrix3 {
-        let angle: f32 = match angle {
-            Angle::Degrees(degrees) => degrees.to_radians(),
-            Angle::Radians(radians) => radians,
-        };
+        let angle = angle.to_radians();
 
         let cos: f32 = f32::cos(angle);
         let sin: f32 = f32::sin(angle);
@@ -114,10 +210,7 @@ impl Rotation for Matrix3 {
     }
 
     fn y_rotation(angle: Angle) -> Matrix3 {
-        let angle: f32 = match angle {
-            Angle::Degrees(degrees) => degrees.to_radians(),
-            Angle::Radians(radians) => radians,
-        };
+        let angle = angle.to_radians();
 
         let cos: f32 = f32::cos(angle);
         let sin: f32 = f32::sin(angle);
@@ -126,10 +219,7 @@ impl Rotation for Matrix3 {
     }
 
     fn z_rotation(angle: Angle) -> Matrix3 {
-        let angle: f32 = match angle {
-            Angle::Degrees(degrees) => degrees.to_radians(),
-            Angle::Radians(radians) => radians,
-        };
+        let angle = angle.to_radians();
 
         let cos: f32 = f32::cos(angle);
         let sin: f32 = f32::sin(angle);
@@ -142,6 +232,34 @@ impl Rotation for Matrix3 {
     }
 }
 
+impl Shear for Matrix3 {
+    type Output = Matrix3;
+
+    fn shear_xy(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, factor, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0)
+    }
+
+    fn shear_xz(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, factor, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0)
+    }
+
+    fn shear_yx(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, factor, 1.0, 0.0, 0.0, 0.0, 1.0)
+    }
+
+    fn shear_yz(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, 0.0, 1.0, factor, 0.0, 0.0, 1.0)
+    }
+
+    fn shear_zx(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, factor, 0.0, 1.0)
+    }
+
+    fn shear_zy(factor: f32) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, factor, 1.0)
+    }
+}
+
 // Matrix-Vector Multiplication
 impl Mul<Vector3> for Matrix3 {
     type Output = Vector3;
@@ -301,6 +419,17 @@ impl Index<usize> for Matrix3 {
     }
 }
 
+impl IndexMut<usize> for Matrix3 {
+    fn index_mut(&mut self, index: usize) -> &mut Vector3 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Matrix3
 where
@@ -323,11 +452,42 @@ where
     }
 }
 
+#[cfg(test)]
 mod tests {
+    use approx::assert_abs_diff_eq;
+
     use crate::matrix::matrix2::Matrix2;
 
     use super::*;
 
+    #[test]
+    fn approx_eq_test() {
+        // Four quarter turns accumulate rounding error
+        let quarter = Matrix3::y_rotation(Angle::Degrees(90.0));
+        let m = quarter * quarter * quarter * quarter;
+        assert_ne!(m, Matrix3::identity());
+        assert!(m.approx_eq(&Matrix3::identity(), 1e-6));
+        assert!(!m.approx_eq(&quarter, 1e-6));
+    }
+
+    #[test]
+    fn display_test() {
+        let m = Matrix3::new(1.0, -2.0, 3.5, 10.0, 0.0, 6.0, 7.0, 8.0, -9.25);
+        assert_eq!(
+            m.to_string(),
+            "[    1,    -2,   3.5]\n[   10,     0,     6]\n[    7,     8, -9.25]"
+        );
+        assert_eq!(
+            format!("{m:.1}"),
+            "[ 1.0, -2.0,  3.5]\n[10.0,  0.0,  6.0]\n[ 7.0,  8.0, -9.2]"
+        );
+    }
+
+    #[test]
+    fn default_test() {
+        assert_eq!(Matrix3::default(), Matrix3::identity());
+    }
+
     #[test]
     fn indexing_test() {
         let x = Vector3::new(10, 5, 2);
@@ -408,6 +568,24 @@ mod tests {
         );
         assert_eq!(a * 2., res);
         assert_eq!(2. * a, res);
+
+        // Scaling commutes, so both sides give exactly the same matrix
+        assert_eq!(2. * a, a * 2.);
+    }
+
+    #[test]
+    fn uniform_scale_test() {
+        let v = Vector3::new(1, 2, 3);
+        let res = Vector3::new(2.5, 5.0, 7.5);
+        assert_eq!(Matrix3::scale(2.5) * v, res);
+    }
+
+    #[test]
+    fn nonuniform_scale_test() {
+        let v = Vector3::new(1, 2, 3);
+        let res = Vector3::new(2, 1, -3);
+        assert_eq!(Matrix3::nonuniform(2.0, 0.5, -1.0) * v, res);
+        assert_eq!(Matrix3::nonuniform(3.0, 3.0, 3.0), Matrix3::scale(3.0));
     }
 
     #[test]
@@ -452,4 +630,254 @@ mod tests {
         assert_eq!(a - b, res);
         assert_eq!(b - a, -res);
     }
+
+    #[test]
+    fn index_mut_test() {
+        let mut m = Matrix3::identity();
+        m[2] = Vector3::new(1, 2, 3);
+        m[0][1] = 5.0;
+        assert_eq!(m.col(2), Vector3::new(1, 2, 3));
+        assert_eq!(m[0][1], 5.0);
+        assert_eq!(m.row(1)[0], 5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut m = Matrix3::identity();
+        m[3] = Vector3::new(1, 2, 3);
+    }
+
+    #[test]
+    fn col_mut_test() {
+        let mut m = Matrix3::identity();
+        *m.col_mut(1) = Vector3::new(3, 4, 5);
+        m.col_mut(0)[1] = 2.0;
+
+        assert_eq!(m.col(1), Vector3::new(3, 4, 5));
+        assert_eq!(m.col(0)[1], 2.0);
+        assert_eq!(m.row(0), Vector3::new(1, 3, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn col_mut_out_of_range_test() {
+        let mut m = Matrix3::identity();
+        *m.col_mut(3) = Vector3::new(1, 2, 3);
+    }
+
+    #[test]
+    fn array_test() {
+        let m = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let cols = [1.0, 4.0, 7.0, 2.0, 5.0, 8.0, 3.0, 6.0, 9.0];
+        let rows = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+
+        assert_eq!(m.to_cols_array(), cols);
+        assert_eq!(m.to_rows_array(), rows);
+        assert_eq!(Matrix3::from_cols_array(cols), m);
+        assert_eq!(Matrix3::from_rows_array(rows), m);
+
+        // Each layout is the other layout of the transpose
+        assert_eq!(m.transpose().to_cols_array(), rows);
+        assert_eq!(Matrix3::from_cols_array(rows), m.transpose());
+    }
+
+    #[test]
+    fn shear_test() {
+        let corners = [
+            Vector3::new(0, 0, 0),
+            Vector3::new(1, 0, 0),
+            Vector3::new(1, 1, 0),
+            Vector3::new(0, 1, 0),
+        ];
+
+        // The top edge of the unit square slides right by half its height
+        let skewed = corners.map(|corner| Matrix3::shear_xy(0.5) * corner);
+        assert_eq!(
+            skewed,
+            [
+                Vector3::new(0, 0, 0),
+                Vector3::new(1, 0, 0),
+                Vector3::new(1.5, 1, 0),
+                Vector3::new(0.5, 1, 0),
+            ]
+        );
+
+        // The right edge slides up instead
+        let skewed = corners.map(|corner| Matrix3::shear_yx(2.0) * corner);
+        assert_eq!(
+            skewed,
+            [
+                Vector3::new(0, 0, 0),
+                Vector3::new(1, 2, 0),
+                Vector3::new(1, 3, 0),
+                Vector3::new(0, 1, 0),
+            ]
+        );
+
+        // The square lies in z = 0, so shearing by z does nothing
+        assert_eq!(
+            corners.map(|corner| Matrix3::shear_xz(3.0) * corner),
+            corners
+        );
+        assert_eq!(
+            Matrix3::shear_zy(-1.0) * Vector3::new(0, 1, 0),
+            Vector3::new(0, 1, -1)
+        );
+    }
+
+    #[test]
+    fn solve_test() {
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let a = Matrix3::new(2.0, 1.0, -1.0, -3.0, -1.0, 2.0, -2.0, 1.0, 2.0);
+        let x = a.solve(Vector3::new(8, -11, -3)).unwrap();
+        assert_abs_diff_eq!(x, Vector3::new(2, 3, -1), epsilon = 1e-5);
+        assert_abs_diff_eq!(a * x, Vector3::new(8, -11, -3), epsilon = 1e-5);
+
+        // Needs a row swap, the first pivot is zero
+        let a = Matrix3::new(0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 2.0);
+        let x = a.solve(Vector3::new(4, 5, 6)).unwrap();
+        assert_abs_diff_eq!(x, Vector3::new(5, 4, 3));
+    }
+
+    #[test]
+    fn solve_singular_test() {
+        // The rows are linearly dependent
+        let a = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        assert_eq!(a.solve(Vector3::new(1, 2, 3)), None);
+
+        let zero = Matrix3::from_cols_array([0.0; 9]);
+        assert_eq!(zero.solve(Vector3::new(0, 0, 0)), None);
+    }
+
+    #[test]
+    fn to_euler_test() {
+        for (yaw, pitch, roll) in [
+            (0.0, 0.0, 0.0),
+            (30.0, 45.0, 60.0),
+            (-120.0, -10.0, 170.0),
+            (90.0, 89.0, -45.0),
+            (5.0, -75.0, -179.0),
+        ] {
+            let m = Matrix3::rotation(
+                Angle::Degrees(yaw),
+                Angle::Degrees(pitch),
+                Angle::Degrees(roll),
+            );
+            let (y, p, r) = m.to_euler();
+
+            assert_abs_diff_eq!(y.to_radians(), f32::to_radians(yaw), epsilon = 1e-3);
+            assert_abs_diff_eq!(p.to_radians(), f32::to_radians(pitch), epsilon = 1e-3);
+            assert_abs_diff_eq!(r.to_radians(), f32::to_radians(roll), epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn to_euler_equivalent_test() {
+        // Pitches past 90 degrees come back as a different set of angles for the same rotation
+        let m = Matrix3::rotation(
+            Angle::Degrees(20.0),
+            Angle::Degrees(120.0),
+            Angle::Degrees(-30.0),
+        );
+        let (yaw, pitch, roll) = m.to_euler();
+        assert_abs_diff_eq!(pitch.to_radians(), f32::to_radians(60.0), epsilon = 1e-4);
+        assert_abs_diff_eq!(Matrix3::rotation(yaw, pitch, roll), m, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn gimbal_lock_test() {
+        for pitch in [90.0, -90.0] {
+            let m = Matrix3::rotation(
+                Angle::Degrees(40.0),
+                Angle::Degrees(pitch),
+                Angle::Degrees(25.0),
+            );
+            let (yaw, recovered, roll) = m.to_euler();
+
+            // The roll is folded into the yaw, which still gives the same rotation
+            assert_eq!(roll, Angle::Radians(0.0));
+            assert_abs_diff_eq!(
+                recovered.to_radians(),
+                f32::to_radians(pitch),
+                epsilon = 1e-3
+            );
+            assert_abs_diff_eq!(Matrix3::rotation(yaw, recovered, roll), m, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn orthonormalize_test() {
+        let rotation = Matrix3::rotation(
+            Angle::Degrees(30.0),
+            Angle::Degrees(-50.0),
+            Angle::Degrees(70.0),
+        );
+        let mut drifted = rotation;
+        drifted.x.y += 0.02;
+        drifted.y.z -= 0.03;
+        drifted.z.x += 0.01;
+
+        let m = drifted.orthonormalize();
+        let cols = [m.x, m.y, m.z];
+        for (i, a) in cols.iter().enumerate() {
+            assert_abs_diff_eq!(a.length(), 1.0, epsilon = 1e-6);
+            for b in &cols[i + 1..] {
+                assert_abs_diff_eq!(a.dot(*b), 0.0, epsilon = 1e-6);
+            }
+        }
+
+        // Still right-handed and close to where it drifted from
+        assert_abs_diff_eq!(m.x.cross(m.y), m.z, epsilon = 1e-6);
+        assert_abs_diff_eq!(m, rotation, epsilon = 0.05);
+
+        // A matrix that's already a rotation stays put
+        assert_abs_diff_eq!(rotation.orthonormalize(), rotation, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn from_diagonal_test() {
+        assert_eq!(
+            Matrix3::from_diagonal(Vector3::new(1, 1, 1)),
+            Matrix3::identity()
+        );
+
+        let m = Matrix3::from_diagonal(Vector3::new(2, -0.5, 3));
+        assert_eq!(m * Vector3::new(3, 4, -1), Vector3::new(6, -2, -3));
+        assert_eq!(
+            m.to_rows_array(),
+            [2.0, 0.0, 0.0, 0.0, -0.5, 0.0, 0.0, 0.0, 3.0]
+        );
+
+        // Same as scaling when every component is the same
+        assert_eq!(
+            Matrix3::from_diagonal(Vector3::new(4, 4, 4)),
+            Matrix3::scale(4.0)
+        );
+    }
+
+    #[test]
+    fn trace_test() {
+        let m = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, -9.5);
+        assert_eq!(m.trace(), -3.5);
+
+        // The trace of a rotation by theta is 1 + 2 cos(theta)
+        let rotation = Matrix3::z_rotation(Angle::Degrees(60.0));
+        assert_abs_diff_eq!(rotation.trace(), 2.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn symmetric_test() {
+        let m = Matrix3::new(2.0, -1.0, 0.5, -1.0, 3.0, 4.0, 0.5, 4.0, 1.0);
+        assert!(m.is_symmetric(0.0));
+        assert!(Matrix3::from_diagonal(Vector3::new(1, 2, 3)).is_symmetric(0.0));
+
+        let rotation = Matrix3::z_rotation(Angle::Degrees(30.0));
+        assert!(!rotation.is_symmetric(1e-4));
+
+        // A matrix times its transpose always is
+        let product = rotation * Matrix3::nonuniform(2.0, 1.0, 0.5);
+        assert!(!product.is_symmetric(1e-4));
+        assert!((product * product.transpose()).is_symmetric(1e-5));
+    }
 }
@@ -1,18 +1,23 @@
-use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub},
+};
 
 use approx::{AbsDiffEq, abs_diff_eq};
 
 use crate::{
     matrix::{
-        matrix::Matrix,
+        matrix::{Matrix, solve_rows, write_rows},
         matrix3::Matrix3,
         rotation::{Angle, Rotation},
         scale::Scale,
+        shear::Shear,
     },
-    vector::{vector3::Vector3, vector4::Vector4},
+    vector::{simd, vector::Vector, vector3::Vector3, vector4::Vector4},
 };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix4 {
     pub x: Vector4,
     pub y: Vector4,
@@ -21,6 +26,7 @@ pub struct Matrix4 {
 }
 
 impl Matrix4 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m00: f32,
         m01: f32,
@@ -59,6 +65,25 @@ impl Matrix4 {
         Self { x, y, z, w }
     }
 
+    // Column-major, every 4 entries are one column in the same order as the x, y, z, w fields
+    pub fn from_cols_array(entries: [f32; 16]) -> Matrix4 {
+        let col = |i: usize| Vector4::from_array(array::from_fn(|j| entries[4 * i + j]));
+        Matrix4::from_cols(col(0), col(1), col(2), col(3))
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 16] {
+        array::from_fn(|i| self[i / 4][i % 4])
+    }
+
+    // Row-major, every 4 entries are one row like the arguments of new
+    pub fn from_rows_array(entries: [f32; 16]) -> Matrix4 {
+        Matrix4::from_cols_array(entries).transpose()
+    }
+
+    pub fn to_rows_array(&self) -> [f32; 16] {
+        self.transpose().to_cols_array()
+    }
+
     pub fn to_homogenous(mat: Matrix3) -> Matrix4 {
         let x = Vector4::to_vector4(mat.x, 0.0);
         let y = Vector4::to_vector4(mat.y, 0.0);
@@ -90,10 +115,15 @@ impl Matrix for Matrix4 {
             0 => self.x,
             1 => self.y,
             2 => self.z,
+            3 => self.w,
             _ => panic!("Out of range"),
         }
     }
 
+    fn col_mut(&mut self, index: usize) -> &mut Vector4 {
+        &mut self[index]
+    }
+
     fn transpose(&self) -> Matrix4 {
         Matrix4::from_rows(self.x, self.y, self.z, self.w)
     }
@@ -105,6 +135,23 @@ impl Matrix for Matrix4 {
         let w = Vector4::new(0, 0, 0, 1);
         Matrix4::from_cols(x, y, z, w)
     }
+
+    fn from_diagonal(v: Vector4) -> Self {
+        let x = Vector4::new(v.x, 0, 0, 0);
+        let y = Vector4::new(0, v.y, 0, 0);
+        let z = Vector4::new(0, 0, v.z, 0);
+        let w = Vector4::new(0, 0, 0, v.w);
+        Matrix4::from_cols(x, y, z, w)
+    }
+
+    fn trace(&self) -> f32 {
+        self.x.x + self.y.y + self.z.z + self.w.w
+    }
+
+    fn solve(&self, b: Vector4) -> Option<Vector4> {
+        let rows = array::from_fn(|i| self.row(i).to_array());
+        solve_rows(rows, b.to_array()).map(Vector4::from_array)
+    }
 }
 
 impl Matrix4 {
@@ -116,6 +163,11 @@ impl Matrix4 {
         Matrix4::from_cols(x, y, z, w)
     }
 
+    // Scales each axis independently, leaving w untouched
+    pub fn nonuniform(sx: f32, sy: f32, sz: f32) -> Matrix4 {
+        Matrix3::nonuniform(sx, sy, sz).homogenous()
+    }
+
     pub fn view(yaw: Angle, pitch: Angle, roll: Angle, t: Vector3) -> Matrix4 {
         Matrix4::rotation(yaw, pitch, roll).transpose() * Matrix4::translation(-t)
     }
@@ -124,11 +176,35 @@ impl Matrix4 {
         Matrix4::translation(t) * Matrix4::rotation(yaw, pitch, roll)
     }
 
+    // View matrix of a camera at eye looking at target, with up pointing roughly up on screen.
+    // The camera looks down -z of view space like view does.
+    pub fn look_at(eye: Vector3, target: Vector3, up: Vector3) -> Matrix4 {
+        let forward = (target - eye).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(forward);
+
+        Matrix4::new(
+            right.x,
+            right.y,
+            right.z,
+            -right.dot(eye),
+            up.x,
+            up.y,
+            up.z,
+            -up.dot(eye),
+            -forward.x,
+            -forward.y,
+            -forward.z,
+            forward.dot(eye),
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
     pub fn perspective(fov: Angle, z_far: f32, z_near: f32, aspect: f32) -> Matrix4 {
-        let fov: f32 = match fov {
-            Angle::Degrees(degrees) => degrees.to_radians(),
-            Angle::Radians(radians) => radians,
-        };
+        let fov = fov.to_radians();
 
         let tan = f32::tan(fov / 2.0);
 
@@ -142,6 +218,32 @@ impl Matrix4 {
         Matrix4::from_cols(x, y, z, w)
     }
 
+    // Maps normalized device coordinates onto a width by height grid, the y axis is flipped
+    // since rows go down the screen. Pixel centers sit on whole numbers, so the edges of the
+    // NDC cube land half a pixel outside of them, and depth is remapped from [-1, 1] to [0, 1].
+    pub fn viewport(width: usize, height: usize) -> Matrix4 {
+        let (width, height) = (width as f32, height as f32);
+
+        Matrix4::new(
+            width / 2.0,
+            0.0,
+            0.0,
+            width / 2.0 - 0.5,
+            0.0,
+            -height / 2.0,
+            0.0,
+            height / 2.0 - 0.5,
+            0.0,
+            0.0,
+            0.5,
+            0.5,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
     pub fn orthographic(t: f32, b: f32, r: f32, l: f32, f: f32, n: f32) -> Matrix4 {
         let x = Vector4::new(2.0 / (r - l), 0, 0, 0);
         let y = Vector4::new(0, 2.0 / (t - b), 0, 0);
@@ -151,6 +253,20 @@ impl Matrix4 {
     }
 }
 
+// Defaults to the identity rather than zero, since multiplying by a zero matrix would
+// collapse every point to the origin instead of leaving it unchanged
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::identity()
+    }
+}
+
+impl fmt::Display for Matrix4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_rows(f, array::from_fn(|i| self.row(i).to_array()))
+    }
+}
+
 impl Scale for Matrix4 {
     type Output = Matrix4;
 
@@ -179,12 +295,42 @@ impl Rotation for Matrix4 {
     }
 }
 
+// Shears only the x, y, z part so translations are left alone
+impl Shear for Matrix4 {
+    type Output = Matrix4;
+
+    fn shear_xy(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_xy(factor))
+    }
+
+    fn shear_xz(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_xz(factor))
+    }
+
+    fn shear_yx(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_yx(factor))
+    }
+
+    fn shear_yz(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_yz(factor))
+    }
+
+    fn shear_zx(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_zx(factor))
+    }
+
+    fn shear_zy(factor: f32) -> Matrix4 {
+        Matrix4::to_homogenous(Matrix3::shear_zy(factor))
+    }
+}
+
 // Matrix-Vector Multiplication
 impl Mul<Vector4> for Matrix4 {
     type Output = Vector4;
 
     fn mul(self, v: Vector4) -> Self::Output {
-        v.x * self.x + v.y * self.y + v.z * self.z + v.w * self.w
+        let cols = [self.x, self.y, self.z, self.w].map(|col| col.to_array());
+        Vector4::from_array(simd::transform(cols, v.to_array()))
     }
 }
 
@@ -192,9 +338,7 @@ impl Mul<Vector3> for Matrix4 {
     type Output = Vector3;
 
     fn mul(self, v: Vector3) -> Self::Output {
-        let v = v.homogenous();
-        let v = v.x * self.x + v.y * self.y + v.z * self.z + v.w * self.w;
-        v.cartesian()
+        (self * v.homogenous()).cartesian()
     }
 }
 
@@ -360,6 +504,18 @@ impl Index<usize> for Matrix4 {
     }
 }
 
+impl IndexMut<usize> for Matrix4 {
+    fn index_mut(&mut self, index: usize) -> &mut Vector4 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            3 => &mut self.w,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Matrix4
 where
@@ -386,13 +542,26 @@ where
     }
 }
 
+#[cfg(test)]
 mod tests {
-    use core::f32;
-
     use approx::assert_abs_diff_eq;
 
     use super::*;
 
+    #[test]
+    fn approx_eq_test() {
+        // A full turn doesn't land exactly back on the identity
+        let m = Matrix4::z_rotation(Angle::Degrees(360.0));
+        assert_ne!(m, Matrix4::identity());
+        assert!(m.approx_eq(&Matrix4::identity(), 1e-6));
+        assert!(!m.approx_eq(&Matrix4::scale(1.001), 1e-6));
+    }
+
+    #[test]
+    fn default_test() {
+        assert_eq!(Matrix4::default(), Matrix4::identity());
+    }
+
     #[test]
     fn indexing_test() {
         let x = Vector3::new(10, 5, 2);
@@ -450,6 +619,30 @@ mod tests {
         assert_abs_diff_eq!(Matrix4::translation(t), res)
     }
 
+    #[test]
+    fn uniform_scale_test() {
+        let v = Vector4::new(1, 2, 3, 1);
+        let res = Vector4::new(2, 4, 6, 1);
+        assert_eq!(Matrix4::scale(2.0) * v, res);
+    }
+
+    #[test]
+    fn nonuniform_scale_test() {
+        let v = Vector4::new(1, 2, 3, 1);
+        let res = Vector4::new(2, 1, -3, 1);
+        assert_eq!(Matrix4::nonuniform(2.0, 0.5, -1.0) * v, res);
+        assert_eq!(Matrix4::nonuniform(3.0, 3.0, 3.0), Matrix4::scale(3.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let m = Matrix4::perspective(Angle::Degrees(60.0), 10.0, 0.05, 2.0)
+            * Matrix4::translation(Vector3::new(1, -2, 3));
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix4>(&json).unwrap(), m);
+    }
+
     #[test]
     fn matrix_vector_multiplication_test() {
         let a = Matrix4::new(
@@ -485,88 +678,259 @@ mod tests {
         );
         assert_abs_diff_eq!(a * 2., res);
         assert_abs_diff_eq!(2. * a, res);
+
+        // Scaling commutes, so both sides give exactly the same matrix
+        assert_eq!(2. * a, a * 2.);
+    }
+
+    #[test]
+    fn matrix_scalar_division_test() {
+        let a = Matrix4::new(
+            2.0, 4.0, 6.0, 8.0, 4.0, 2.0, 1.0, 10.0, 6.0, 4.0, 2.0, 8.0, 1.0, 2.0, 4.0, 8.0,
+        );
+        let b = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 2.0, 1.0, 0.5, 5.0, 3.0, 2.0, 1.0, 4.0, 0.5, 1.0, 2.0, 4.0,
+        );
+        let c = Matrix4::new(
+            1.0, 0.5, 0.33333334, 0.25, 0.5, 1.0, 2.0, 0.2, 0.33333334, 0.5, 1.0, 0.25, 2.0, 1.0,
+            0.5, 0.25,
+        );
+        assert_eq!(a / 2., b);
+        assert_eq!(2. / a, c);
+    }
+
+    #[test]
+    fn matrix_scalar_addition_test() {
+        let a = Matrix4::new(
+            1.0, 2.0, 3.0, 9.5, 2.5, 12.0, 29.0, 55.0, 11.5, 3.0, 123.5, 12.0, 43.5, 31.5, 5.5, 1.0,
+        );
+        let res = Matrix4::new(
+            3.0, 4.0, 5.0, 11.5, 4.5, 14.0, 31.0, 57.0, 13.5, 5.0, 125.5, 14.0, 45.5, 33.5, 7.5,
+            3.0,
+        );
+        assert_eq!(a + 2., res);
+        assert_eq!(2. + a, res);
     }
 
-    // #[test]
-    // fn matrix_scalar_division_test() {
-    //     let a = Matrix2::new(2.0, 4.0, 6.0, 4.0);
-    //     let b = Matrix2::new(1.0, 2.0, 3.0, 2.0);
-    //     let c = Matrix2::new(1.0, 0.5, 0.33333334, 0.5);
-    //     assert_eq!(a / 2., b);
-    //     assert_eq!(2. / a, c);
-    // }
-
-    // #[test]
-    // fn matrix_scalar_addition_test() {
-    //     let a = Matrix3::new(
-    //         1.0, 2.0, 3.0,
-    //         2.1, 12.0, 29.0,
-    //         11.1, 3.0, 123.5,
-    //     );
-    //     let res = Matrix3::new(
-    //         3.0, 4.0, 5.0,
-    //         4.1, 14.0, 31.0,
-    //         13.1, 5.0, 125.5,
-    //     );
-    //     assert_eq!(a + 2., res);
-    //     assert_eq!(2. + a, res);
-    // }
-
-    // #[test]
-    // fn matrix_scalar_subtraction_test() {
-    //     let a = Matrix3::new(
-    //         1.0, 2.0, 3.0,
-    //         5.0, 12.0, 29.0,
-    //         11.1, 3.0, 123.5,
-    //     );
-    //     let res = Matrix3::new(
-    //         -1.0, 0.0, 1.0,
-    //         3.0, 10.0, 27.0,
-    //         9.1, 1.0, 121.5,
-    //     );
-    //     assert_eq!(a - 2., res);
-    //     assert_eq!(2. - a, -res);
-    // }
-
-    // #[test]
-    // fn matrix_addition_test() {
-    //     let a = Matrix3::new(
-    //         1.0, 2.0, 3.0,
-    //         2.11, 12.0, 29.0,
-    //         11.1, 3.0, 123.5,
-    //     );
-    //     let b = Matrix3::new(
-    //         5.5, 1.1, 6.0,
-    //         9.5, 111.0, 74.0,
-    //         81.1, 99.0, -2.0,
-    //     );
-    //     let res = Matrix3::new(
-    //         6.5, 3.1, 9.0,
-    //         11.61, 123.0, 103.0,
-    //         92.2, 102.0, 121.5,
-    //     );
-    //     assert_eq!(a + b, res);
-    //     assert_eq!(b + a, res);
-    // }
-
-    // #[test]
-    // fn matrix_subtraction_test() {
-    //     let a = Matrix3::new(
-    //         1.0, 2.0, 3.0,
-    //         2.1, 12.0, 29.0,
-    //         11.1, 3.0, 123.5,
-    //     );
-    //     let b = Matrix3::new(
-    //         5.5, 1.1, 6.0,
-    //         9.5, 111.0, 74.0,
-    //         81.1, 99.0, -2.0,
-    //     );
-    //     let res = Matrix3::new(
-    //         -4.5, 0.9, -3.0,
-    //         -7.4, -99.0, -45.0,
-    //         -70.0, -96.0, 125.5,
-    //     );
-    //     assert_eq!(a - b, res);
-    //     assert_eq!(b - a, -res);
-    // }
+    #[test]
+    fn matrix_scalar_subtraction_test() {
+        let a = Matrix4::new(
+            1.0, 2.0, 3.0, 9.5, 5.0, 12.0, 29.0, 55.0, 11.5, 3.0, 123.5, 12.0, 43.5, 31.5, 5.5, 1.0,
+        );
+        let res = Matrix4::new(
+            -1.0, 0.0, 1.0, 7.5, 3.0, 10.0, 27.0, 53.0, 9.5, 1.0, 121.5, 10.0, 41.5, 29.5, 3.5,
+            -1.0,
+        );
+        assert_eq!(a - 2., res);
+        assert_eq!(2. - a, -res);
+    }
+
+    #[test]
+    fn matrix_addition_test() {
+        let a = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 2.5, 12.0, 29.0, 0.0, 11.5, 3.0, 123.5, -1.0, 0.0, 0.0, 0.0, 1.0,
+        );
+        let b = Matrix4::new(
+            5.5, 1.5, 6.0, -4.0, 9.5, 111.0, 74.0, 2.0, 81.5, 99.0, -2.0, 1.0, 3.0, 2.0, 1.0, 1.0,
+        );
+        let res = Matrix4::new(
+            6.5, 3.5, 9.0, 0.0, 12.0, 123.0, 103.0, 2.0, 93.0, 102.0, 121.5, 0.0, 3.0, 2.0, 1.0,
+            2.0,
+        );
+        assert_eq!(a + b, res);
+        assert_eq!(b + a, res);
+    }
+
+    #[test]
+    fn matrix_subtraction_test() {
+        let a = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 2.5, 12.0, 29.0, 0.0, 11.5, 3.0, 123.5, -1.0, 0.0, 0.0, 0.0, 1.0,
+        );
+        let b = Matrix4::new(
+            5.5, 1.5, 6.0, -4.0, 9.5, 111.0, 74.0, 2.0, 81.5, 99.0, -2.0, 1.0, 3.0, 2.0, 1.0, 1.0,
+        );
+        let res = Matrix4::new(
+            -4.5, 0.5, -3.0, 8.0, -7.0, -99.0, -45.0, -2.0, -70.0, -96.0, 125.5, -2.0, -3.0, -2.0,
+            -1.0, 0.0,
+        );
+        assert_eq!(a - b, res);
+        assert_eq!(b - a, -res);
+    }
+
+    #[test]
+    fn index_mut_test() {
+        let mut m = Matrix4::identity();
+        m[3] = Vector4::new(1, 2, 3, 4);
+        m[0][1] = 5.0;
+        assert_eq!(m.col(3), Vector4::new(1, 2, 3, 4));
+        assert_eq!(m[0][1], 5.0);
+        assert_eq!(m.row(1)[0], 5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut m = Matrix4::identity();
+        m[4] = Vector4::new(1, 2, 3, 4);
+    }
+
+    #[test]
+    fn col_mut_test() {
+        let mut m = Matrix4::identity();
+        *m.col_mut(1) = Vector4::new(3, 4, 5, 6);
+        m.col_mut(0)[1] = 2.0;
+
+        assert_eq!(m.col(1), Vector4::new(3, 4, 5, 6));
+        assert_eq!(m.col(0)[1], 2.0);
+        assert_eq!(m.row(0), Vector4::new(1, 3, 0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn col_mut_out_of_range_test() {
+        let mut m = Matrix4::identity();
+        *m.col_mut(4) = Vector4::new(1, 2, 3, 4);
+    }
+
+    #[test]
+    fn array_test() {
+        let m = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+        );
+        let cols = [
+            1.0, 5.0, 9.0, 13.0, 2.0, 6.0, 10.0, 14.0, 3.0, 7.0, 11.0, 15.0, 4.0, 8.0, 12.0, 16.0,
+        ];
+        let rows = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+        ];
+
+        assert_eq!(m.to_cols_array(), cols);
+        assert_eq!(m.to_rows_array(), rows);
+        assert_eq!(Matrix4::from_cols_array(cols), m);
+        assert_eq!(Matrix4::from_rows_array(rows), m);
+
+        // Each layout is the other layout of the transpose
+        assert_eq!(m.transpose().to_cols_array(), rows);
+        assert_eq!(Matrix4::from_cols_array(rows), m.transpose());
+    }
+
+    #[test]
+    fn shear_test() {
+        let corners = [
+            Vector4::new(0, 0, 0, 1),
+            Vector4::new(1, 0, 0, 1),
+            Vector4::new(1, 1, 0, 1),
+            Vector4::new(0, 1, 0, 1),
+        ];
+
+        let skewed = corners.map(|corner| Matrix4::shear_xy(0.5) * corner);
+        assert_eq!(
+            skewed,
+            [
+                Vector4::new(0, 0, 0, 1),
+                Vector4::new(1, 0, 0, 1),
+                Vector4::new(1.5, 1, 0, 1),
+                Vector4::new(0.5, 1, 0, 1),
+            ]
+        );
+
+        // Shearing after the translation also skews by the translated y
+        let t = Matrix4::translation(Vector3::new(0, 2, 0));
+        let corner = Vector4::new(0, 1, 0, 1);
+        assert_eq!(
+            t * Matrix4::shear_xy(0.5) * corner,
+            Vector4::new(0.5, 3, 0, 1)
+        );
+        assert_eq!(
+            Matrix4::shear_xy(0.5) * t * corner,
+            Vector4::new(1.5, 3, 0, 1)
+        );
+    }
+
+    #[test]
+    fn look_at_test() {
+        // From the origin down -z is no change at all
+        let view = Matrix4::look_at(
+            Vector3::new(0, 0, 0),
+            Vector3::new(0, 0, -1),
+            Vector3::new(0, 1, 0),
+        );
+        assert_abs_diff_eq!(view, Matrix4::identity());
+
+        // Same as a view matrix turned a quarter about y, looking down -x
+        let eye = Vector3::new(3, 1, 2);
+        let view = Matrix4::look_at(eye, Vector3::new(-5, 1, 2), Vector3::new(0, 1, 0));
+        let expected = Matrix4::view(
+            Angle::Degrees(0.0),
+            Angle::Degrees(90.0),
+            Angle::Degrees(0.0),
+            eye,
+        );
+        assert_abs_diff_eq!(view, expected, epsilon = 1e-6);
+
+        // The target ends up straight ahead
+        let target = view * Vector4::new(-5, 1, 2, 1);
+        assert_abs_diff_eq!(target, Vector4::new(0, 0, -8, 1), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn viewport_test() {
+        let viewport = Matrix4::viewport(4, 2);
+        let map = |x: f32, y: f32, z: f32| (viewport * Vector4::new(x, y, z, 1.0)).xyz();
+
+        // Top left and bottom right corners are the outer edges of the corner pixels
+        assert_eq!(map(-1.0, 1.0, -1.0), Vector3::new(-0.5, -0.5, 0.0));
+        assert_eq!(map(1.0, -1.0, 1.0), Vector3::new(3.5, 1.5, 1.0));
+        assert_eq!(map(1.0, 1.0, 0.0), Vector3::new(3.5, -0.5, 0.5));
+        assert_eq!(map(-1.0, -1.0, 0.0), Vector3::new(-0.5, 1.5, 0.5));
+
+        // The center is between the middle pixels
+        assert_eq!(map(0.0, 0.0, 0.0), Vector3::new(1.5, 0.5, 0.5));
+
+        // The center of the top left pixel
+        assert_eq!(map(-0.75, 0.5, 0.0), Vector3::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn solve_test() {
+        let a = Matrix4::translation(Vector3::new(1, 2, 3)) * Matrix4::nonuniform(2.0, 4.0, 0.5);
+        let b = Vector4::new(5, 6, 7, 1);
+        let x = a.solve(b).unwrap();
+        assert_abs_diff_eq!(a * x, b, epsilon = 1e-5);
+        assert_abs_diff_eq!(x, Vector4::new(2, 1, 8, 1), epsilon = 1e-5);
+
+        // Projecting onto a plane throws away a dimension
+        assert_eq!(Matrix4::nonuniform(1.0, 1.0, 0.0).solve(b), None);
+    }
+
+    #[test]
+    fn from_diagonal_test() {
+        assert_eq!(
+            Matrix4::from_diagonal(Vector4::new(1, 1, 1, 1)),
+            Matrix4::identity()
+        );
+
+        let m = Matrix4::from_diagonal(Vector4::new(2, -0.5, 3, 1));
+        assert_eq!(m * Vector4::new(3, 4, -1, 1), Vector4::new(6, -2, -3, 1));
+        assert_eq!(m, Matrix4::nonuniform(2.0, -0.5, 3.0));
+    }
+
+    #[test]
+    fn trace_test() {
+        let m = Matrix4::from_rows_array(array::from_fn(|i| i as f32));
+        assert_eq!(m.trace(), 0.0 + 5.0 + 10.0 + 15.0);
+        assert_eq!(Matrix4::identity().trace(), 4.0);
+    }
+
+    #[test]
+    fn symmetric_test() {
+        assert!(Matrix4::nonuniform(2.0, 3.0, 4.0).is_symmetric(0.0));
+
+        // Translations move the offset into the last column only
+        let translation = Matrix4::translation(Vector3::new(1, 2, 3));
+        assert!(!translation.is_symmetric(1e-6));
+
+        let m = translation + translation.transpose();
+        assert!(m.is_symmetric(0.0));
+    }
 }
@@ -1,6 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod matrix;
 pub mod matrix2;
 pub mod matrix3;
 pub mod matrix4;
+pub mod quaternion;
 pub mod rotation;
 pub mod scale;
+pub mod shear;
@@ -0,0 +1,286 @@
+use std::ops::{Mul, Neg};
+
+use approx::AbsDiffEq;
+
+use crate::{
+    matrix::{matrix3::Matrix3, matrix4::Matrix4, rotation::Angle},
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+// Rotation stored as w + xi + yj + zk, only unit quaternions represent rotations
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quaternion {
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Quaternion {
+        Quaternion { w, x, y, z }
+    }
+
+    pub fn identity() -> Quaternion {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    // Rotates counter-clockwise about the axis, which doesn't need to be unit length
+    pub fn from_axis_angle(axis: Vector3, angle: Angle) -> Quaternion {
+        let angle = angle.to_radians();
+
+        let axis = axis.normalize();
+        let sin = f32::sin(angle / 2.0);
+        Quaternion::new(
+            f32::cos(angle / 2.0),
+            axis.x * sin,
+            axis.y * sin,
+            axis.z * sin,
+        )
+    }
+
+    // Same order as Rotation::rotation, yaw about x first, then pitch about y, then roll about z
+    pub fn from_euler(yaw: Angle, pitch: Angle, roll: Angle) -> Quaternion {
+        let x = Quaternion::from_axis_angle(Vector3::new(1, 0, 0), yaw);
+        let y = Quaternion::from_axis_angle(Vector3::new(0, 1, 0), pitch);
+        let z = Quaternion::from_axis_angle(Vector3::new(0, 0, 1), roll);
+        z * y * x
+    }
+
+    pub fn length(&self) -> f32 {
+        f32::sqrt(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z)
+    }
+
+    pub fn normalize(&self) -> Quaternion {
+        let length = self.length();
+        Quaternion::new(
+            self.w / length,
+            self.x / length,
+            self.y / length,
+            self.z / length,
+        )
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    // Spherical linear interpolation along the shortest arc between two unit quaternions
+    pub fn slerp(a: Quaternion, b: Quaternion, t: f32) -> Quaternion {
+        // q and -q are the same rotation, flipping b keeps the interpolation on the shorter arc
+        let (b, cos) = match a.dot(b) {
+            cos if cos < 0.0 => (-b, -cos),
+            cos => (b, cos),
+        };
+
+        // Nearly parallel quaternions would divide by almost zero, so lerp and renormalize instead
+        if cos > 0.9995 {
+            return Quaternion::new(
+                a.w + (b.w - a.w) * t,
+                a.x + (b.x - a.x) * t,
+                a.y + (b.y - a.y) * t,
+                a.z + (b.z - a.z) * t,
+            )
+            .normalize();
+        }
+
+        let angle = f32::acos(cos);
+        let sin = f32::sin(angle);
+        let weight_a = f32::sin((1.0 - t) * angle) / sin;
+        let weight_b = f32::sin(t * angle) / sin;
+
+        Quaternion::new(
+            a.w * weight_a + b.w * weight_b,
+            a.x * weight_a + b.x * weight_b,
+            a.y * weight_a + b.y * weight_b,
+            a.z * weight_a + b.z * weight_b,
+        )
+    }
+
+    // Inverse rotation for unit quaternions
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn rotate(&self, v: Vector3) -> Vector3 {
+        self.to_matrix3() * v
+    }
+
+    pub fn to_matrix3(&self) -> Matrix3 {
+        let Quaternion { w, x, y, z } = *self;
+
+        Matrix3::new(
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        )
+    }
+
+    pub fn to_matrix4(&self) -> Matrix4 {
+        self.to_matrix3().homogenous()
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+// Hamilton product, applies other first and then self (like matrix multiplication)
+impl Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        )
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Quaternion {
+        Quaternion::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+// For approximate equals
+impl AbsDiffEq for Quaternion {
+    type Epsilon = <f32 as AbsDiffEq>::Epsilon;
+
+    fn default_epsilon() -> Self::Epsilon {
+        Self::Epsilon::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Self::Epsilon::abs_diff_eq(&self.w, &other.w, epsilon)
+            && Self::Epsilon::abs_diff_eq(&self.x, &other.x, epsilon)
+            && Self::Epsilon::abs_diff_eq(&self.y, &other.y, epsilon)
+            && Self::Epsilon::abs_diff_eq(&self.z, &other.z, epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::matrix::{matrix::Matrix, rotation::Rotation};
+
+    use super::*;
+
+    #[test]
+    fn axis_rotation_test() {
+        let angle = Angle::Degrees(37.0);
+
+        let x = Quaternion::from_axis_angle(Vector3::new(1, 0, 0), angle);
+        assert_abs_diff_eq!(x.to_matrix3(), Matrix3::x_rotation(angle), epsilon = 1e-6);
+
+        let y = Quaternion::from_axis_angle(Vector3::new(0, 2, 0), angle);
+        assert_abs_diff_eq!(y.to_matrix3(), Matrix3::y_rotation(angle), epsilon = 1e-6);
+
+        let z = Quaternion::from_axis_angle(Vector3::new(0, 0, 1), angle);
+        assert_abs_diff_eq!(z.to_matrix4(), Matrix4::z_rotation(angle), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn euler_test() {
+        let yaw = Angle::Degrees(30.0);
+        let pitch = Angle::Degrees(-45.0);
+        let roll = Angle::Radians(1.2);
+
+        let q = Quaternion::from_euler(yaw, pitch, roll);
+        assert_abs_diff_eq!(q.length(), 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            q.to_matrix3(),
+            Matrix3::rotation(yaw, pitch, roll),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn composition_test() {
+        let a = Quaternion::from_axis_angle(Vector3::new(1, 2, 3), Angle::Degrees(50.0));
+        let b = Quaternion::from_axis_angle(Vector3::new(-1, 0, 1), Angle::Degrees(-20.0));
+
+        assert_abs_diff_eq!(
+            (a * b).to_matrix3(),
+            a.to_matrix3() * b.to_matrix3(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn rotate_test() {
+        let q = Quaternion::from_axis_angle(Vector3::new(0, 0, 1), Angle::Degrees(90.0));
+        assert_abs_diff_eq!(
+            q.rotate(Vector3::new(1, 0, 0)),
+            Vector3::new(0, 1, 0),
+            epsilon = 1e-6
+        );
+
+        // The conjugate undoes the rotation
+        assert_abs_diff_eq!(q * q.conjugate(), Quaternion::identity(), epsilon = 1e-6);
+        assert_abs_diff_eq!(Quaternion::default().to_matrix3(), Matrix3::identity());
+    }
+
+    #[test]
+    fn normalize_test() {
+        let q = Quaternion::new(1.0, 2.0, 3.0, 4.0).normalize();
+        assert_abs_diff_eq!(q.length(), 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn slerp_endpoints_test() {
+        let a = Quaternion::from_axis_angle(Vector3::new(1, 0, 0), Angle::Degrees(20.0));
+        let b = Quaternion::from_axis_angle(Vector3::new(0, 1, 1), Angle::Degrees(130.0));
+
+        assert_abs_diff_eq!(Quaternion::slerp(a, b, 0.0), a, epsilon = 1e-6);
+        assert_abs_diff_eq!(Quaternion::slerp(a, b, 1.0), b, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn slerp_midpoint_test() {
+        let axis = Vector3::new(0, 0, 1);
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(axis, Angle::Degrees(90.0));
+
+        let mid = Quaternion::slerp(a, b, 0.5);
+        assert_abs_diff_eq!(mid.length(), 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            mid,
+            Quaternion::from_axis_angle(axis, Angle::Degrees(45.0)),
+            epsilon = 1e-6
+        );
+
+        // -b is the same rotation, so it still takes the short way around
+        let flipped = Quaternion::slerp(a, -b, 0.5);
+        assert_abs_diff_eq!(flipped.to_matrix3(), mid.to_matrix3(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn slerp_parallel_test() {
+        let axis = Vector3::new(1, 1, 0);
+        let a = Quaternion::from_axis_angle(axis, Angle::Degrees(10.0));
+        let b = Quaternion::from_axis_angle(axis, Angle::Degrees(10.5));
+
+        let mid = Quaternion::slerp(a, b, 0.5);
+        assert_abs_diff_eq!(mid.length(), 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            mid,
+            Quaternion::from_axis_angle(axis, Angle::Degrees(10.25)),
+            epsilon = 1e-5
+        );
+    }
+}
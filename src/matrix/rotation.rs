@@ -1,11 +1,76 @@
-use crate::matrix::{matrix3::Matrix3, matrix4::Matrix4};
+use std::{
+    f32::consts::TAU,
+    ops::{Add, Mul, Sub},
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Angle {
     Radians(f32),
     Degrees(f32),
 }
 
+impl Angle {
+    pub fn to_radians(&self) -> f32 {
+        match *self {
+            Angle::Radians(radians) => radians,
+            Angle::Degrees(degrees) => degrees.to_radians(),
+        }
+    }
+
+    pub fn to_degrees(&self) -> f32 {
+        match *self {
+            Angle::Radians(radians) => radians.to_degrees(),
+            Angle::Degrees(degrees) => degrees,
+        }
+    }
+
+    // The same direction wrapped into [0, 2π) radians or [0, 360) degrees, keeping the unit
+    pub fn normalized(&self) -> Angle {
+        match *self {
+            Angle::Radians(radians) => Angle::Radians(wrap(radians, TAU)),
+            Angle::Degrees(degrees) => Angle::Degrees(wrap(degrees, 360.0)),
+        }
+    }
+}
+
+// rem_euclid can round up to exactly the full turn for tiny negative values
+fn wrap(value: f32, turn: f32) -> f32 {
+    let wrapped = value.rem_euclid(turn);
+    if wrapped >= turn { 0.0 } else { wrapped }
+}
+
+// Arithmetic keeps the unit of the left hand side, converting the right hand side to it
+impl Add for Angle {
+    type Output = Angle;
+
+    fn add(self, other: Angle) -> Angle {
+        match self {
+            Angle::Radians(radians) => Angle::Radians(radians + other.to_radians()),
+            Angle::Degrees(degrees) => Angle::Degrees(degrees + other.to_degrees()),
+        }
+    }
+}
+
+impl Sub for Angle {
+    type Output = Angle;
+
+    fn sub(self, other: Angle) -> Angle {
+        self + other * -1.0
+    }
+}
+
+impl Mul<f32> for Angle {
+    type Output = Angle;
+
+    fn mul(self, scalar: f32) -> Angle {
+        match self {
+            Angle::Radians(radians) => Angle::Radians(radians * scalar),
+            Angle::Degrees(degrees) => Angle::Degrees(degrees * scalar),
+        }
+    }
+}
+
 pub trait Rotation {
     type Output;
 
@@ -14,3 +79,63 @@ pub trait Rotation {
     fn z_rotation(angle: Angle) -> Self::Output;
     fn rotation(yaw: Angle, pitch: Angle, roll: Angle) -> Self::Output;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn conversion_test() {
+        assert_abs_diff_eq!(Angle::Degrees(180.0).to_radians(), PI);
+        assert_abs_diff_eq!(Angle::Radians(PI / 2.0).to_degrees(), 90.0);
+        assert_eq!(Angle::Degrees(45.0).to_degrees(), 45.0);
+        assert_eq!(Angle::Radians(1.5).to_radians(), 1.5);
+    }
+
+    #[test]
+    fn normalized_test() {
+        assert_abs_diff_eq!(Angle::Degrees(750.0).normalized().to_degrees(), 30.0);
+        assert_abs_diff_eq!(Angle::Degrees(-90.0).normalized().to_degrees(), 270.0);
+        assert_eq!(Angle::Degrees(360.0).normalized(), Angle::Degrees(0.0));
+
+        let Angle::Radians(radians) = Angle::Radians(-7.0 * PI / 2.0).normalized() else {
+            panic!("normalizing changed the unit");
+        };
+        assert_abs_diff_eq!(radians, PI / 2.0, epsilon = 1e-5);
+
+        // Values just under a full turn stay under it
+        let Angle::Radians(radians) = Angle::Radians(-1e-9).normalized() else {
+            panic!("normalizing changed the unit");
+        };
+        assert!((0.0..TAU).contains(&radians));
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        assert_eq!(
+            Angle::Degrees(30.0) + Angle::Degrees(15.0),
+            Angle::Degrees(45.0)
+        );
+        assert_eq!(
+            Angle::Radians(2.0) - Angle::Radians(0.5),
+            Angle::Radians(1.5)
+        );
+        assert_eq!(Angle::Degrees(30.0) * 3.0, Angle::Degrees(90.0));
+        assert_eq!(Angle::Radians(1.0) * -0.5, Angle::Radians(-0.5));
+
+        // Mixed units are converted to the unit on the left
+        let Angle::Degrees(degrees) = Angle::Degrees(90.0) + Angle::Radians(PI) else {
+            panic!("addition changed the unit");
+        };
+        assert_abs_diff_eq!(degrees, 270.0);
+
+        let Angle::Radians(radians) = Angle::Radians(PI) - Angle::Degrees(90.0) else {
+            panic!("subtraction changed the unit");
+        };
+        assert_abs_diff_eq!(radians, PI / 2.0);
+    }
+}
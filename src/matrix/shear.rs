@@ -0,0 +1,11 @@
+// shear_ab offsets the a coordinate by factor times the b coordinate
+pub trait Shear {
+    type Output;
+
+    fn shear_xy(factor: f32) -> Self::Output;
+    fn shear_xz(factor: f32) -> Self::Output;
+    fn shear_yx(factor: f32) -> Self::Output;
+    fn shear_yz(factor: f32) -> Self::Output;
+    fn shear_zx(factor: f32) -> Self::Output;
+    fn shear_zy(factor: f32) -> Self::Output;
+}
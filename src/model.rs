@@ -1,7 +1,24 @@
-use std::fs;
-
-use crate::{Vector2, matrix::rotation::Angle, vector::vector3::Vector3};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
 
+use crate::{
+    Vector2,
+    material::Material,
+    matrix::{
+        matrix4::Matrix4,
+        rotation::{Angle, Rotation},
+        scale::Scale,
+    },
+    triangle::Triangle,
+    vector::{vector::Vector, vector3::Vector3},
+    vertex::Vertex,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct VertexData {
@@ -10,16 +27,89 @@ pub struct VertexData {
     pub normal: Option<usize>,
 }
 
+#[derive(Debug)]
+pub enum ModelError {
+    Io(io::Error),
+    MalformedLine {
+        line_number: usize,
+        content: String,
+    },
+    BadIndex {
+        line_number: usize,
+        index: usize,
+    },
+    // An index stored in a model's data that doesn't point into the given array
+    OutOfRange {
+        array: &'static str,
+        index: usize,
+    },
+    ParseFloat {
+        line_number: usize,
+        content: String,
+    },
+    // Reading a material library failed, line numbers in error are the library's
+    Library {
+        path: String,
+        error: Box<ModelError>,
+    },
+}
+
+impl ModelError {
+    fn malformed(line_number: usize, content: &str) -> ModelError {
+        ModelError::MalformedLine {
+            line_number,
+            content: String::from(content),
+        }
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::Io(err) => write!(f, "could not read or write model: {err}"),
+            ModelError::MalformedLine {
+                line_number,
+                content,
+            } => write!(f, "malformed line {line_number}: {content}"),
+            ModelError::BadIndex { line_number, index } => {
+                write!(f, "index {index} out of range on line {line_number}")
+            }
+            ModelError::OutOfRange { array, index } => {
+                write!(f, "index {index} out of range for {array}")
+            }
+            ModelError::ParseFloat {
+                line_number,
+                content,
+            } => write!(f, "invalid number on line {line_number}: {content}"),
+            ModelError::Library { path, error } => write!(f, "in material library {path}: {error}"),
+        }
+    }
+}
+
+impl Error for ModelError {}
+
+impl From<io::Error> for ModelError {
+    fn from(err: io::Error) -> ModelError {
+        ModelError::Io(err)
+    }
+}
+
 #[derive(Debug)]
 pub struct Model {
     pub data: Vec<(VertexData, VertexData, VertexData)>,
     pub vertices: Vec<Vector3>,
     pub tex_coords: Vec<Vector2>,
     pub normals: Vec<Vector3>,
-    pub transform: Transform
+    // Material name for each triangle in data (set by usemtl)
+    pub face_materials: Vec<Option<String>>,
+    pub materials: HashMap<String, Material>,
+    // Used for triangles without a material, or with one that isn't in materials
+    pub material: Material,
+    pub transform: Transform,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     pub yaw: Angle,
     pub pitch: Angle,
@@ -28,119 +118,398 @@ pub struct Transform {
     pub scale: f32,
 }
 
+// No rotation, at the origin, and unscaled
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            yaw: Angle::Degrees(0.0),
+            pitch: Angle::Degrees(0.0),
+            roll: Angle::Degrees(0.0),
+            position: Vector3::new(0.0, 0.0, 0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    // Model matrix: scales first, then rotates, then translates (T * R * S)
+    pub fn to_matrix(&self) -> Matrix4 {
+        let scalar = Matrix4::scale(self.scale);
+        let rotation = Matrix4::rotation(self.yaw, self.pitch, self.roll);
+        let translation = Matrix4::translation(self.position);
+        translation * rotation * scalar
+    }
+}
+
+impl FromStr for Model {
+    type Err = ModelError;
+
+    // There's no file to find material libraries next to, so mtllib is skipped
+    fn from_str(contents: &str) -> Result<Model, ModelError> {
+        Model::parse(contents, None)
+    }
+}
 
 impl Model {
-    pub fn load(path: &str) -> Option<Model> {
-        // Reading obj file
-        let data: Vec<String> = match fs::read_to_string(path) {
-            Ok(data) => data.lines().map(String::from).collect(),
-            Err(_) => return Option::None,
-        };
+    pub fn load(path: &str) -> Result<Model, ModelError> {
+        // Material libraries are relative to the obj file
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        Model::parse(&fs::read_to_string(path)?, Some(directory))
+    }
 
+    // Reads the contents of an obj file, mtllib is skipped like in from_str
+    pub fn from_reader(mut reader: impl Read) -> Result<Model, ModelError> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        contents.parse()
+    }
+
+    // Material libraries are looked up in directory. Missing ones are ignored since exported
+    // models often name a library that wasn't shipped with them, other errors are returned.
+    // Without a directory mtllib is skipped, so only models loaded from a file read other files
+    fn parse(contents: &str, directory: Option<&Path>) -> Result<Model, ModelError> {
         let mut vertices: Vec<Vector3> = Vec::new();
         let mut normals: Vec<Vector3> = Vec::new();
         let mut tex_coords: Vec<Vector2> = Vec::new();
-        let mut faces: Vec<Vec<&str>> = Vec::new();
+        let mut faces: Vec<(usize, Vec<VertexData>, Option<String>)> = Vec::new();
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        let mut material: Option<String> = None;
 
-        for line in &data {
-            let line = line.split_whitespace().collect::<Vec<&str>>();
+        for (index, content) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let line = content.split_whitespace().collect::<Vec<&str>>();
 
-            if line.len() == 0 {
+            let Some((command, parameters)) = line.split_first() else {
                 continue;
-            }
-
-            let (command, parameters) = line.split_first().expect("Incomplete line");
+            };
 
             match *command {
-                "v" => {
-                    if let Some(vertex) = Model::to_vector3(&parameters.to_vec()) {
-                        vertices.push(vertex);
-                    }
-                }
-                "vn" => {
-                    if let Some(normal) = Model::to_vector3(&parameters.to_vec()) {
-                        normals.push(normal);
+                "v" => vertices.push(Model::to_vector3(parameters, line_number, content)?),
+                "vn" => normals.push(Model::to_vector3(parameters, line_number, content)?),
+                "vt" => tex_coords.push(Model::to_vector2(parameters, line_number, content)?),
+                "f" => {
+                    if parameters.len() < 3 {
+                        return Err(ModelError::malformed(line_number, content));
                     }
+
+                    let face = parameters
+                        .iter()
+                        .map(|vertex| Model::to_vertex_data(vertex, line_number, content))
+                        .collect::<Result<Vec<VertexData>, ModelError>>()?;
+
+                    faces.push((line_number, face, material.clone()));
                 }
+                "mtllib" => {
+                    let Some(directory) = directory else {
+                        continue;
+                    };
 
-                "vt" => {
-                    if let Some(tex_coord) = Model::to_vector2(&parameters.to_vec()) {
-                        tex_coords.push(tex_coord);
+                    for name in parameters {
+                        let library = directory.join(name);
+                        match Material::load_library(&library.to_string_lossy()) {
+                            Ok(library) => materials.extend(library),
+                            Err(ModelError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {}
+                            Err(err) => {
+                                return Err(ModelError::Library {
+                                    path: library.display().to_string(),
+                                    error: Box::new(err),
+                                });
+                            }
+                        }
                     }
                 }
-                "f" => faces.push(parameters.to_vec()),
+                "usemtl" => material = parameters.first().map(|name| String::from(*name)),
                 _ => continue,
             }
         }
 
         let mut data: Vec<(VertexData, VertexData, VertexData)> = Vec::new();
-        for face in &faces {
-            let mut f: Vec<VertexData> = Vec::new();
-            
-            for vertex in face {
-                let vertex = vertex
-                    .split("/")
-                    .into_iter()
-                    .map(|s| s.parse::<usize>().ok())
-                    .collect::<Vec<Option<usize>>>();
-                
-                let pos = (*vertex.get(0).unwrap()).unwrap();
-                let tex_coord = *vertex.get(1).unwrap_or(&None);
-                let normal = *vertex.get(2).unwrap_or(&None);
-                    
-                let vertex = VertexData {
-                    pos,
-                    tex_coord,
-                    normal,
-                };
-
-                f.push(vertex);
-            }
+        let mut face_materials: Vec<Option<String>> = Vec::new();
+        for (line_number, f, material) in &faces {
+            // Indices are 1-based and may refer to elements defined after the face
+            for vertex in f {
+                Model::check_index(vertex.pos, vertices.len(), *line_number)?;
+
+                if let Some(index) = vertex.tex_coord {
+                    Model::check_index(index, tex_coords.len(), *line_number)?;
+                }
 
-            if f.len() == 3 {
-                data.push((f[0], f[1], f[2]));
+                if let Some(index) = vertex.normal {
+                    Model::check_index(index, normals.len(), *line_number)?;
+                }
             }
 
-            if f.len() == 4 {
-                data.push((f[0], f[1], f[2]));
-                data.push((f[0], f[2], f[3]));
+            // Fan triangulate the face (v0, vi, vi+1) so quads and larger polygons are kept
+            for i in 1..f.len().saturating_sub(1) {
+                data.push((f[0], f[i], f[i + 1]));
+                face_materials.push(material.clone());
             }
         }
 
-        let transform = Transform {
-            yaw: Angle::Degrees(0.0),
-            pitch: Angle::Degrees(0.0),
-            roll: Angle::Degrees(0.0),
-            position: Vector3::new(0.0, 0.0, 0.0),
-            scale: 1.0
-        };
-
         let model = Model {
             data,
             vertices,
             normals,
             tex_coords,
-            transform
+            face_materials,
+            materials,
+            material: Material::new("default"),
+            transform: Transform::default(),
+        };
+
+        Ok(model)
+    }
+
+    pub(crate) fn to_floats(
+        parameters: &[&str],
+        count: usize,
+        line_number: usize,
+        content: &str,
+    ) -> Result<Vec<f32>, ModelError> {
+        if parameters.len() < count {
+            return Err(ModelError::malformed(line_number, content));
+        }
+
+        // Extra components (e.g. w or vertex colors) are ignored
+        parameters[..count]
+            .iter()
+            .map(|s| {
+                s.parse::<f32>().map_err(|_| ModelError::ParseFloat {
+                    line_number,
+                    content: String::from(content),
+                })
+            })
+            .collect()
+    }
+
+    fn to_vector3(
+        parameters: &[&str],
+        line_number: usize,
+        content: &str,
+    ) -> Result<Vector3, ModelError> {
+        let vert = Model::to_floats(parameters, 3, line_number, content)?;
+        Ok(Vector3::new(vert[0], vert[1], vert[2]))
+    }
+
+    fn to_vector2(
+        parameters: &[&str],
+        line_number: usize,
+        content: &str,
+    ) -> Result<Vector2, ModelError> {
+        let vert = Model::to_floats(parameters, 2, line_number, content)?;
+        Ok(Vector2::new(vert[0], vert[1]))
+    }
+
+    // Parses a face vertex in the v, v/vt, v//vn or v/vt/vn form
+    fn to_vertex_data(
+        vertex: &str,
+        line_number: usize,
+        content: &str,
+    ) -> Result<VertexData, ModelError> {
+        let mut indices = vertex.split('/');
+
+        let mut next_index = || -> Result<Option<usize>, ModelError> {
+            match indices.next() {
+                None | Some("") => Ok(None),
+                Some(index) => match index.parse::<usize>() {
+                    Ok(index) => Ok(Some(index)),
+                    Err(_) => Err(ModelError::malformed(line_number, content)),
+                },
+            }
+        };
+
+        let pos = next_index()?.ok_or_else(|| ModelError::malformed(line_number, content))?;
+        let tex_coord = next_index()?;
+        let normal = next_index()?;
+
+        Ok(VertexData {
+            pos,
+            tex_coord,
+            normal,
+        })
+    }
+
+    fn check_index(index: usize, len: usize, line_number: usize) -> Result<(), ModelError> {
+        if index == 0 || index > len {
+            return Err(ModelError::BadIndex { line_number, index });
+        }
+
+        Ok(())
+    }
+
+    // Writes the geometry as an obj file that load reads back into the same vertices and
+    // triangles. Faces keep their usemtl names, but no mtllib is written and the transform
+    // isn't applied.
+    pub fn save(&self, path: &str) -> Result<(), ModelError> {
+        let mut obj = String::new();
+
+        for v in &self.vertices {
+            obj.push_str(&format!("v {} {} {}\n", v.x, v.y, v.z));
+        }
+
+        for vt in &self.tex_coords {
+            obj.push_str(&format!("vt {} {}\n", vt.x, vt.y));
+        }
+
+        for vn in &self.normals {
+            obj.push_str(&format!("vn {} {} {}\n", vn.x, vn.y, vn.z));
+        }
+
+        let mut material = None;
+        for (i, (a, b, c)) in self.data.iter().enumerate() {
+            // A bare usemtl goes back to faces without a material
+            let face_material = self.face_materials.get(i).and_then(Option::as_ref);
+            if material != face_material {
+                match face_material {
+                    Some(name) => obj.push_str(&format!("usemtl {name}\n")),
+                    None => obj.push_str("usemtl\n"),
+                }
+                material = face_material;
+            }
+
+            let [a, b, c] = [a, b, c].map(Model::to_face_vertex);
+            obj.push_str(&format!("f {a} {b} {c}\n"));
+        }
+
+        fs::write(path, obj)?;
+        Ok(())
+    }
+
+    // Formats a face vertex in the v, v/vt, v//vn or v/vt/vn form
+    fn to_face_vertex(vertex: &VertexData) -> String {
+        match (vertex.tex_coord, vertex.normal) {
+            (None, None) => format!("{}", vertex.pos),
+            (Some(vt), None) => format!("{}/{vt}", vertex.pos),
+            (None, Some(vn)) => format!("{}//{vn}", vertex.pos),
+            (Some(vt), Some(vn)) => format!("{}/{vt}/{vn}", vertex.pos),
+        }
+    }
+
+    // Fills in smooth per-vertex normals for every face vertex without one, by
+    // accumulating the geometric normals of the faces sharing each vertex position. Fails
+    // without changing the model if a position index is out of range
+    pub fn compute_normals(&mut self) -> Result<(), ModelError> {
+        let missing = self
+            .data
+            .iter()
+            .any(|(a, b, c)| a.normal.is_none() || b.normal.is_none() || c.normal.is_none());
+
+        if !missing {
+            return Ok(());
+        }
+
+        let mut accumulated = vec![Vector3::new(0, 0, 0); self.vertices.len()];
+        for (a, b, c) in &self.data {
+            let pa = *Vertex::lookup(&self.vertices, a.pos, "vertices")?;
+            let pb = *Vertex::lookup(&self.vertices, b.pos, "vertices")?;
+            let pc = *Vertex::lookup(&self.vertices, c.pos, "vertices")?;
+
+            // Degenerate faces have no meaningful normal and are skipped
+            let Some(normal) = (pb - pa).cross(pc - pa).try_normalize() else {
+                continue;
+            };
+
+            // Weighted by the angle at each corner so triangulating a face doesn't skew the result
+            let corners = [
+                (a.pos, pa, pb, pc),
+                (b.pos, pb, pc, pa),
+                (c.pos, pc, pa, pb),
+            ];
+            for (index, p, next, prev) in corners {
+                let cos = (next - p).normalize().dot((prev - p).normalize());
+                let angle = f32::acos(cos.clamp(-1.0, 1.0));
+                accumulated[index - 1] += normal * angle;
+            }
+        }
+
+        // Computed normals are appended so existing normal indices stay valid
+        let offset = self.normals.len();
+        for normal in accumulated {
+            self.normals.push(normal.try_normalize().unwrap_or(normal));
+        }
+
+        for (a, b, c) in &mut self.data {
+            for vertex in [a, b, c] {
+                if vertex.normal.is_none() {
+                    vertex.normal = Some(offset + vertex.pos);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every triangle of the model in object space, with the vertex attributes it refers to.
+    // Loaded models always have valid indices, but data can be changed afterwards.
+    pub fn triangles(&self) -> impl Iterator<Item = Result<Triangle<'_>, ModelError>> {
+        self.data.iter().map(|(a, b, c)| {
+            Ok(Triangle {
+                a: Vertex::new(a, self)?,
+                b: Vertex::new(b, self)?,
+                c: Vertex::new(c, self)?,
+            })
+        })
+    }
+
+    // Material the triangle at index i of data is shaded with
+    pub fn face_material(&self, i: usize) -> &Material {
+        self.face_materials
+            .get(i)
+            .and_then(Option::as_ref)
+            .and_then(|name| self.materials.get(name))
+            .unwrap_or(&self.material)
+    }
+
+    // Smallest and largest corners of the box around the vertices, both zero without vertices
+    pub fn bounding_box(&self) -> (Vector3, Vector3) {
+        let Some(first) = self.vertices.first() else {
+            return (Vector3::new(0, 0, 0), Vector3::new(0, 0, 0));
         };
 
-        Some(model)
+        self.vertices
+            .iter()
+            .fold((*first, *first), |(min, max), v| (min.min(*v), max.max(*v)))
+    }
+
+    // Center and radius of a sphere around the bounding box after the transform, in world space
+    pub fn bounding_sphere(&self) -> (Vector3, f32) {
+        let (min, max) = self.bounding_box();
+        let center = self.transform.to_matrix() * ((min + max) / 2.0).homogenous();
+        let radius = (max - min).length() / 2.0 * self.transform.scale.abs();
+        (center.xyz(), radius)
     }
 
-    fn to_vector3(vert: &Vec<&str>) -> Option<Vector3> {
-        let vert: Vec<f32> = vert.iter().filter_map(|s| s.parse::<f32>().ok()).collect();
-        if vert.len() < 3 {
-            return None;
+    // Moves the vertices so their average position is the origin
+    pub fn recenter(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        let sum = self
+            .vertices
+            .iter()
+            .fold(Vector3::new(0, 0, 0), |sum, v| sum + *v);
+        let centroid = sum / self.vertices.len() as f32;
+
+        for v in &mut self.vertices {
+            *v -= centroid;
         }
-        Some(Vector3::new(vert[0], vert[1], vert[2]))
     }
 
-    fn to_vector2(vert: &Vec<&str>) -> Option<Vector2> {
-        let vert: Vec<f32> = vert.iter().filter_map(|s| s.parse::<f32>().ok()).collect();
-        if vert.len() < 2 {
-            return None;
+    // Scales the vertices about the origin so the longest side of the bounding box is 1
+    pub fn normalize_scale(&mut self) {
+        let (min, max) = self.bounding_box();
+        let size = max - min;
+        let extent = size.x.max(size.y).max(size.z);
+
+        // A single point or no vertices at all can't be scaled up
+        if extent <= f32::EPSILON {
+            return;
         }
 
-        Some(Vector2::new(vert[0], vert[1]))
+        for v in &mut self.vertices {
+            *v /= extent;
+        }
     }
 
     pub fn set_scale(&mut self, scale: f32) {
@@ -148,24 +517,15 @@ impl Model {
     }
 
     pub fn rotate_x(&mut self, yaw: f32) {
-        self.transform.yaw = match self.transform.yaw {
-            Angle::Radians(value) => Angle::Radians(value),
-            Angle::Degrees(value) => Angle::Degrees(value + yaw),
-        };
+        self.transform.yaw = self.transform.yaw + Angle::Degrees(yaw);
     }
 
     pub fn rotate_y(&mut self, pitch: f32) {
-        self.transform.pitch = match self.transform.pitch {
-            Angle::Radians(value) => Angle::Radians(value),
-            Angle::Degrees(value) => Angle::Degrees(value + pitch),
-        };
+        self.transform.pitch = self.transform.pitch + Angle::Degrees(pitch);
     }
 
     pub fn rotate_z(&mut self, roll: f32) {
-        self.transform.roll = match self.transform.roll {
-            Angle::Radians(value) => Angle::Radians(value),
-            Angle::Degrees(value) => Angle::Degrees(value + roll),
-        };
+        self.transform.roll = self.transform.roll + Angle::Degrees(roll);
     }
 
     pub fn set_position(&mut self, position: Vector3) {
@@ -173,7 +533,517 @@ impl Model {
     }
 
     pub fn translate(&mut self, amount: Vector3) {
-        self.transform.position = self.transform.position + amount;
+        self.transform.position += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::Vector4;
+
+    use super::*;
+
+    fn positions(model: &Model) -> Vec<(usize, usize, usize)> {
+        model
+            .data
+            .iter()
+            .map(|(a, b, c)| (a.pos, b.pos, c.pos))
+            .collect()
+    }
+
+    #[test]
+    fn material_test() {
+        let directory = env::temp_dir().join("renderer_material_test");
+        fs::create_dir_all(&directory).unwrap();
+
+        let mtl = "newmtl Red\nKd 1.0 0.0 0.0\n\nnewmtl Blue\nKa 0.1 0.1 0.1\nKd 0.0 0.0 1.0\n";
+        let obj = "mtllib colors.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nf 1 2 3\nusemtl Red\nf 2 4 3\nusemtl Blue\nf 1 2 4 3\n";
+        fs::write(directory.join("colors.mtl"), mtl).unwrap();
+        fs::write(directory.join("colors.obj"), obj).unwrap();
+
+        let model = Model::load(directory.join("colors.obj").to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            model.face_materials,
+            vec![
+                None,
+                Some(String::from("Red")),
+                Some(String::from("Blue")),
+                Some(String::from("Blue")),
+            ]
+        );
+
+        assert_eq!(model.materials.len(), 2);
+        assert_eq!(model.materials["Red"].diffuse_color, Vector3::new(1, 0, 0));
+        assert_eq!(model.materials["Blue"].diffuse_color, Vector3::new(0, 0, 1));
+
+        // Faces without a material get the model's default one
+        assert_eq!(model.face_material(0).name, "default");
+        assert_eq!(model.face_material(1).name, "Red");
+        assert_eq!(model.face_material(3).name, "Blue");
+    }
+
+    #[test]
+    fn missing_material_library_test() {
+        let directory = env::temp_dir().join("renderer_missing_material_test");
+        fs::create_dir_all(&directory).unwrap();
+        let obj = "mtllib missing.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl Missing\nf 1 2 3\n";
+        fs::write(directory.join("missing.obj"), obj).unwrap();
+
+        let model = Model::load(directory.join("missing.obj").to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(model.materials.is_empty());
+        assert_eq!(model.face_materials, vec![Some(String::from("Missing"))]);
+    }
+
+    #[test]
+    fn malformed_material_library_test() {
+        let directory = env::temp_dir().join("renderer_malformed_material_test");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("bad.mtl"), "newmtl Red\n\nKd 1 zero 0\n").unwrap();
+        fs::write(directory.join("bad.obj"), "mtllib bad.mtl\nv 0 0 0\n").unwrap();
+
+        let result = Model::load(directory.join("bad.obj").to_str().unwrap());
+        fs::remove_dir_all(&directory).unwrap();
+
+        // The line number is the library's, not the obj file's
+        let Err(ModelError::Library { path, error }) = result else {
+            panic!("expected a library error");
+        };
+        assert!(path.ends_with("bad.mtl"));
+        assert!(matches!(
+            *error,
+            ModelError::ParseFloat { line_number: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn in_memory_material_library_test() {
+        // Even a library with an absolute path isn't read for models parsed from memory
+        let directory = env::temp_dir().join("renderer_in_memory_material_test");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("colors.mtl"), "newmtl Red\nKd 1 0 0\n").unwrap();
+        let library = directory.join("colors.mtl");
+        let obj = format!(
+            "mtllib {}\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl Red\nf 1 2 3\n",
+            library.display()
+        );
+
+        let parsed = Model::from_str(&obj).unwrap();
+        let read = Model::from_reader(obj.as_bytes()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(parsed.materials.is_empty());
+        assert!(read.materials.is_empty());
+        assert_eq!(parsed.face_materials, vec![Some(String::from("Red"))]);
+    }
+
+    #[test]
+    fn missing_file_test() {
+        let result = Model::load("does/not/exist.obj");
+        assert!(matches!(result, Err(ModelError::Io(_))));
+    }
+
+    #[test]
+    fn malformed_line_test() {
+        let result = Model::from_str("v 0 0 0\nv 1 0\n");
+        assert!(matches!(
+            result,
+            Err(ModelError::MalformedLine { line_number: 2, ref content }) if content == "v 1 0"
+        ));
+
+        let result = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 a 3\n");
+        assert!(matches!(
+            result,
+            Err(ModelError::MalformedLine { line_number: 4, .. })
+        ));
+
+        let result = Model::from_str("v 0 0 0\nv 1 0 0\nf 1 2\n");
+        assert!(matches!(
+            result,
+            Err(ModelError::MalformedLine { line_number: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_float_test() {
+        let result = Model::from_str("v 0 0 0\nvn 0 one 0\n");
+        assert!(matches!(
+            result,
+            Err(ModelError::ParseFloat { line_number: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn bad_index_test() {
+        let result = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n");
+        assert!(matches!(
+            result,
+            Err(ModelError::BadIndex {
+                line_number: 4,
+                index: 4
+            })
+        ));
+
+        let result = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n");
+        assert!(matches!(result, Err(ModelError::BadIndex { index: 0, .. })));
+
+        let result = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//2\n");
+        assert!(matches!(result, Err(ModelError::BadIndex { index: 2, .. })));
+    }
+
+    #[test]
+    fn face_formats_test() {
+        let model =
+            Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1 2/1 3//1\n").unwrap();
+        let (a, b, c) = model.data[0];
+        assert_eq!((a.pos, a.tex_coord, a.normal), (1, None, None));
+        assert_eq!((b.pos, b.tex_coord, b.normal), (2, Some(1), None));
+        assert_eq!((c.pos, c.tex_coord, c.normal), (3, None, Some(1)));
+    }
+
+    #[test]
+    fn whitespace_test() {
+        // Aligned columns, indentation, tabs and trailing spaces
+        let model = Model::from_str(
+            "v  1.0  -2.0   3.0\n   v 0 1 0  \n\tv\t0\t0\t1\n\n  \n\
+             vn   0  0  1\nf  1//1   2//1  3//1 \n",
+        )
+        .unwrap();
+        assert_eq!(model.vertices[0], Vector3::new(1, -2, 3));
+        assert_eq!(model.vertices.len(), 3);
+        assert_eq!(model.normals, [Vector3::new(0, 0, 1)]);
+        assert_eq!(positions(&model), [(1, 2, 3)]);
+    }
+
+    #[test]
+    fn crlf_test() {
+        // The last line has no line feed after its carriage return
+        let model =
+            Model::from_str("v 0 0 0\r\nv 1 0 0\r\nv 0 1 0\r\nusemtl red\r\nf 1 2 3\r").unwrap();
+        assert_eq!(model.vertices[2], Vector3::new(0, 1, 0));
+        assert_eq!(positions(&model), [(1, 2, 3)]);
+        assert_eq!(model.face_materials, [Some(String::from("red"))]);
     }
 
+    #[test]
+    fn compute_plane_normals_test() {
+        let mut model =
+            Model::from_str("v 0 0 0\nv 0 0 1\nv 1 0 1\nv 1 0 0\nv 5 5 5\nf 1 2 3 4\nf 1 1 5\n")
+                .unwrap();
+        model.compute_normals().unwrap();
+
+        for (a, b, c) in &model.data {
+            for vertex in [a, b, c] {
+                let normal = model.normals[vertex.normal.unwrap() - 1];
+                if vertex.pos == 5 {
+                    // Only part of the degenerate face, so nothing is accumulated
+                    assert_eq!(normal, Vector3::new(0, 0, 0));
+                } else {
+                    assert_abs_diff_eq!(normal, Vector3::new(0, 1, 0), epsilon = 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compute_cube_normals_test() {
+        let mut model = Model::from_str(concat!(
+            "v 1 -1 -1\nv 1 -1 1\nv -1 -1 1\nv -1 -1 -1\n",
+            "v 1 1 -1\nv 1 1 1\nv -1 1 1\nv -1 1 -1\n",
+            "f 1 2 3 4\nf 5 8 7 6\nf 1 5 6 2\nf 2 6 7 3\nf 3 7 8 4\nf 5 1 4 8\n",
+        ))
+        .unwrap();
+        model.compute_normals().unwrap();
+
+        // Every corner normal points outwards along its diagonal
+        for (a, b, c) in &model.data {
+            for vertex in [a, b, c] {
+                let normal = model.normals[vertex.normal.unwrap() - 1];
+                let outward = model.vertices[vertex.pos - 1].normalize();
+                assert_abs_diff_eq!(normal, outward, epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn keep_existing_normals_test() {
+        let mut model =
+            Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 1 0 0\nf 1//1 2//1 3//1\n").unwrap();
+        model.compute_normals().unwrap();
+
+        assert_eq!(model.normals, vec![Vector3::new(1, 0, 0)]);
+    }
+
+    #[test]
+    fn compute_normals_bad_index_test() {
+        let mut model = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+        for index in [0, 4] {
+            model.data[0].1.pos = index;
+            let result = model.compute_normals();
+            assert!(matches!(
+                result,
+                Err(ModelError::OutOfRange { array: "vertices", index: i }) if i == index
+            ));
+
+            // The model is left as it was
+            assert!(model.normals.is_empty());
+            assert!(model.data[0].0.normal.is_none());
+        }
+    }
+
+    #[test]
+    fn triangle_face_test() {
+        let model = Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        assert_eq!(positions(&model), vec![(1, 2, 3)]);
+    }
+
+    #[test]
+    fn quad_face_test() {
+        let model = Model::from_str(concat!(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n",
+            "vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\n",
+            "f 1/1/1 2/2/1 3/3/1 4/4/1\n",
+        ))
+        .unwrap();
+        assert_eq!(positions(&model), vec![(1, 2, 3), (1, 3, 4)]);
+
+        // Texture coordinate and normal indices are kept for each generated triangle
+        let (a, b, c) = model.data[1];
+        assert_eq!(
+            (a.tex_coord, b.tex_coord, c.tex_coord),
+            (Some(1), Some(3), Some(4))
+        );
+        assert_eq!((a.normal, b.normal, c.normal), (Some(1), Some(1), Some(1)));
+    }
+
+    #[test]
+    fn pentagon_face_test() {
+        let model =
+            Model::from_str("v 0 0 0\nv 1 0 0\nv 2 1 0\nv 1 2 0\nv 0 1 0\nf 1 2 3 4 5\n").unwrap();
+        assert_eq!(positions(&model), vec![(1, 2, 3), (1, 3, 4), (1, 4, 5)]);
+    }
+
+    #[test]
+    fn identity_transform_test() {
+        let transform = Transform::default();
+        assert_eq!(transform.position, Vector3::new(0, 0, 0));
+        assert_eq!(transform.scale, 1.0);
+
+        let p = Vector4::new(1, 2, 3, 1);
+        assert_abs_diff_eq!(transform.to_matrix() * p, p);
+    }
+
+    #[test]
+    fn to_matrix_test() {
+        let transform = Transform {
+            yaw: Angle::Degrees(0.0),
+            pitch: Angle::Degrees(0.0),
+            roll: Angle::Degrees(90.0),
+            position: Vector3::new(10, 0, -5),
+            scale: 2.0,
+        };
+
+        // Scaled to (2, 0, 0), rotated about z to (0, 2, 0), then translated
+        let p = Vector4::new(1, 0, 0, 1);
+        let res = Vector4::new(10, 2, -5, 1);
+        assert_abs_diff_eq!(transform.to_matrix() * p, res, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn rotate_test() {
+        let mut model: Model = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3".parse().unwrap();
+        model.rotate_x(30.0);
+        model.rotate_x(15.0);
+        assert_eq!(model.transform.yaw, Angle::Degrees(45.0));
+
+        // Rotations stored in radians are turned by the degrees too
+        model.transform.roll = Angle::Radians(0.0);
+        model.rotate_z(180.0);
+        assert_abs_diff_eq!(model.transform.roll.to_radians(), std::f32::consts::PI);
+        assert!(matches!(model.transform.roll, Angle::Radians(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let transform = Transform {
+            yaw: Angle::Degrees(30.0),
+            pitch: Angle::Radians(0.5),
+            roll: Angle::Degrees(-90.0),
+            position: Vector3::new(1.5, -2, 3),
+            scale: 0.25,
+        };
+
+        let json = serde_json::to_string(&transform).unwrap();
+        assert_eq!(serde_json::from_str::<Transform>(&json).unwrap(), transform);
+    }
+
+    #[test]
+    fn save_test() {
+        let mut model = Model::from_str(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0.125\nv 0.1 -2.5 1e-3\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvn 0 0 1\n\
+             usemtl red\nf 1/1/1 2/2/1 3/3/1 4/2/1\nusemtl\nf 1//1 3//1 5//1\nf 2 3 5\n\
+             usemtl blue\nf 1/3 2/2 5/1\n",
+        )
+        .unwrap();
+
+        let indices = |model: &Model| -> Vec<_> {
+            model
+                .data
+                .iter()
+                .flat_map(|(a, b, c)| [a, b, c].map(|v| (v.pos, v.tex_coord, v.normal)))
+                .collect()
+        };
+
+        // Every face vertex form as loaded, then with the computed normals filled in
+        for compute_normals in [false, true] {
+            if compute_normals {
+                model.compute_normals().unwrap();
+            }
+
+            let path = env::temp_dir().join("renderer_save_saved.obj");
+            model.save(path.to_str().unwrap()).unwrap();
+            let saved = Model::load(path.to_str().unwrap()).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(saved.vertices, model.vertices);
+            assert_eq!(saved.tex_coords, model.tex_coords);
+            assert_eq!(saved.normals, model.normals);
+            assert_eq!(saved.face_materials, model.face_materials);
+            assert_eq!(indices(&saved), indices(&model));
+        }
+    }
+
+    #[test]
+    fn bounding_box_test() {
+        let model = Model::from_str("v 1 2 3\nv -4 5 0.5\nv 2 -1 8\nf 1 2 3\n").unwrap();
+        assert_eq!(
+            model.bounding_box(),
+            (Vector3::new(-4, -1, 0.5), Vector3::new(2, 5, 8))
+        );
+
+        let empty = Model::from_str("").unwrap();
+        let zero = Vector3::new(0, 0, 0);
+        assert_eq!(empty.bounding_box(), (zero, zero));
+    }
+
+    #[test]
+    fn recenter_normalize_test() {
+        // A 4 by 2 by 1 box centered on (10, -5, 3)
+        let mut model = Model::from_str(
+            "v 8 -6 2.5\nv 12 -6 2.5\nv 12 -4 2.5\nv 8 -4 2.5\n\
+             v 8 -6 3.5\nv 12 -6 3.5\nv 12 -4 3.5\nv 8 -4 3.5\n",
+        )
+        .unwrap();
+
+        model.recenter();
+        let (min, max) = model.bounding_box();
+        assert_abs_diff_eq!(min, Vector3::new(-2, -1, -0.5), epsilon = 1e-5);
+        assert_abs_diff_eq!(max, Vector3::new(2, 1, 0.5), epsilon = 1e-5);
+
+        let sum = model
+            .vertices
+            .iter()
+            .fold(Vector3::new(0, 0, 0), |sum, v| sum + *v);
+        assert_abs_diff_eq!(sum, Vector3::new(0, 0, 0), epsilon = 1e-5);
+
+        model.normalize_scale();
+        let (min, max) = model.bounding_box();
+        assert_abs_diff_eq!(max - min, Vector3::new(1, 0.5, 0.25), epsilon = 1e-5);
+        assert_abs_diff_eq!(min + max, Vector3::new(0, 0, 0), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn triangles_test() {
+        let model = Model::from_str(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0.5 0.5\nvn 0 0 1\n\
+             f 1/1/1 2/1/1 3/1/1 4/1/1\nf 1 3 2\n",
+        )
+        .unwrap();
+
+        let triangles: Vec<Triangle> = model.triangles().map(Result::unwrap).collect();
+        assert_eq!(triangles.len(), 3);
+
+        // The quad is split into (1, 2, 3) and (1, 3, 4)
+        let second = &triangles[1];
+        assert_eq!(*second.a.pos, Vector3::new(0, 0, 0));
+        assert_eq!(*second.b.pos, Vector3::new(1, 1, 0));
+        assert_eq!(*second.c.pos, Vector3::new(0, 1, 0));
+        assert_eq!(second.a.tex_coord, Some(&Vector2::new(0.5, 0.5)));
+        assert_eq!(second.c.normal, Some(&Vector3::new(0, 0, 1)));
+
+        let last = &triangles[2];
+        assert_eq!(*last.b.pos, Vector3::new(1, 1, 0));
+        assert_eq!((last.a.tex_coord, last.a.normal), (None, None));
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        let mut model =
+            Model::from_str("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//1\n").unwrap();
+
+        // Loading checks the indices, so break them afterwards
+        model.data[0].1.pos = 4;
+        let result = Vertex::new(&model.data[0].1, &model);
+        assert!(matches!(
+            result,
+            Err(ModelError::OutOfRange {
+                array: "vertices",
+                index: 4
+            })
+        ));
+
+        model.data[0].1.pos = 2;
+        model.data[0].2.normal = Some(0);
+        let mut triangles = model.triangles();
+        assert!(matches!(
+            triangles.next(),
+            Some(Err(ModelError::OutOfRange {
+                array: "normals",
+                index: 0
+            }))
+        ));
+
+        let err = Vertex::new(&model.data[0].2, &model).unwrap_err();
+        assert_eq!(err.to_string(), "index 0 out of range for normals");
+    }
+
+    #[test]
+    fn from_str_test() {
+        let model: Model = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvn 0 0 1\nf 1//1 2//1 4//1 3//1\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(model.vertices.len(), 4);
+        assert_eq!(model.normals.len(), 1);
+        assert_eq!(positions(&model), vec![(1, 2, 4), (1, 4, 3)]);
+
+        // Errors are the same as when loading a file
+        let result = Model::from_str("v 0 0 0\nf 1 2 3\n");
+        assert!(matches!(result, Err(ModelError::BadIndex { index: 2, .. })));
+    }
+
+    #[test]
+    fn from_reader_test() {
+        let obj: &[u8] = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let model = Model::from_reader(obj).unwrap();
+        assert_eq!(model.vertices.len(), 3);
+        assert_eq!(model.data.len(), 1);
+
+        // Contents that aren't UTF-8 can't be read
+        let invalid: &[u8] = &[b'v', b' ', 0xff, 0xfe];
+        assert!(matches!(
+            Model::from_reader(invalid),
+            Err(ModelError::Io(_))
+        ));
+    }
 }
@@ -0,0 +1,945 @@
+use std::ops::Range;
+
+use crate::{
+    Grid, Vector2,
+    light::{Shading, intensity, ramp_char},
+    matrix::matrix4::Matrix4,
+    triangle::Triangle,
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+// Cell types that triangles can be rasterized into
+pub trait Rasterizable: Clone {
+    // Cells that nothing is drawn on
+    const BACKGROUND: Self;
+
+    // Drawn along the edges of triangles in wireframe mode
+    const WIREFRAME: Self;
+
+    // Value of a point of a triangle that's lit with an intensity in [0, 1]
+    fn shaded(intensity: f32, shading: &Shading) -> Self;
+
+    // One cell from the covered samples of a block of count super-samples, the samples that
+    // aren't given were left uncovered and count as unlit
+    fn average(samples: &[Self], count: usize, ramp: &str) -> Self;
+}
+
+// Text, a character of the shading's ramp
+impl Rasterizable for char {
+    const BACKGROUND: char = ' ';
+
+    const WIREFRAME: char = '#';
+
+    fn shaded(intensity: f32, shading: &Shading) -> char {
+        ramp_char(intensity, shading.ramp)
+    }
+
+    // Averaged by brightness, characters that aren't in the ramp are unlit
+    fn average(samples: &[char], count: usize, ramp: &str) -> char {
+        let last = ramp.chars().count().saturating_sub(1).max(1) as f32;
+        let total: f32 = samples
+            .iter()
+            .filter_map(|ch| ramp.chars().position(|c| c == *ch))
+            .map(|index| index as f32 / last)
+            .sum();
+
+        ramp_char(total / count.max(1) as f32, ramp)
+    }
+}
+
+// Coverage masks, every point of a triangle is set however it's lit
+impl Rasterizable for bool {
+    const BACKGROUND: bool = false;
+
+    const WIREFRAME: bool = true;
+
+    fn shaded(_: f32, _: &Shading) -> bool {
+        true
+    }
+
+    fn average(samples: &[bool], _: usize, _: &str) -> bool {
+        samples.contains(&true)
+    }
+}
+
+// Keeps track of coverage, None wherever nothing was drawn
+impl<T: Rasterizable> Rasterizable for Option<T> {
+    const BACKGROUND: Option<T> = None;
+
+    const WIREFRAME: Option<T> = Some(T::WIREFRAME);
+
+    fn shaded(intensity: f32, shading: &Shading) -> Option<T> {
+        Some(T::shaded(intensity, shading))
+    }
+
+    fn average(samples: &[Option<T>], count: usize, ramp: &str) -> Option<T> {
+        let covered: Vec<T> = samples.iter().flatten().cloned().collect();
+        if covered.is_empty() {
+            return None;
+        }
+
+        Some(T::average(&covered, count, ramp))
+    }
+}
+
+// Which fragments are drawn, going by the stencil at their cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StencilTest {
+    Always,
+    // Only where the stencil is set
+    Set,
+    // Only where the stencil is clear
+    Clear,
+}
+
+impl StencilTest {
+    pub fn passes(&self, stencil: bool) -> bool {
+        match self {
+            StencilTest::Always => true,
+            StencilTest::Set => stencil,
+            StencilTest::Clear => !stencil,
+        }
+    }
+}
+
+// What drawing a fragment does to the stencil at its cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StencilOp {
+    Keep,
+    Set,
+    Clear,
+}
+
+impl StencilOp {
+    pub fn apply(&self, stencil: bool) -> bool {
+        match self {
+            StencilOp::Keep => stencil,
+            StencilOp::Set => true,
+            StencilOp::Clear => false,
+        }
+    }
+}
+
+// A stencil buffer the same size as the grid, with how fragments test and write it. Fragments
+// that fail the test are discarded before the depth test, and the op is only applied to the
+// ones that end up drawn, so one pass can mark cells that a later pass is limited to (or kept
+// out of).
+pub struct Stencil<'a> {
+    pub buffer: &'a mut Grid<bool>,
+    pub test: StencilTest,
+    pub op: StencilOp,
+}
+
+impl Stencil<'_> {
+    // Whether the fragment at (x, y) passes, cells outside of the buffer count as clear
+    fn passes(&self, x: usize, y: usize) -> bool {
+        self.test
+            .passes(self.buffer.get(x, y).copied().unwrap_or(false))
+    }
+
+    fn write(&mut self, x: usize, y: usize) {
+        if let Some(stencil) = self.buffer.get_mut(x, y) {
+            *stencil = self.op.apply(*stencil);
+        }
+    }
+}
+
+// Rectangle of cells that rasterizing is limited to, for drawing into part of a larger grid.
+// x and y are the top left cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scissor {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Scissor {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Scissor {
+        Scissor {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // The same area of a grid with factor times the cells on each axis
+    pub fn scale(&self, factor: usize) -> Scissor {
+        Scissor::new(
+            self.x * factor,
+            self.y * factor,
+            self.width * factor,
+            self.height * factor,
+        )
+    }
+
+    // Inclusive (min_x, min_y, max_x, max_y) of the cells that are in both the rectangle and a
+    // width by height grid, or None when there aren't any
+    pub fn bounds(&self, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+        let max_x = self.x.saturating_add(self.width).min(width);
+        let max_y = self.y.saturating_add(self.height).min(height);
+
+        if self.x >= max_x || self.y >= max_y {
+            return None;
+        }
+
+        Some((self.x, self.y, max_x - 1, max_y - 1))
+    }
+}
+
+// Per-vertex attributes that can be blended across a triangle
+pub trait Interpolate: Sized {
+    // Value at a point of a triangle given its barycentric weights for a, b and c
+    fn barycentric_lerp(a: Self, b: Self, c: Self, weights: Vector3) -> Self;
+
+    // a when t is 0 and b when t is 1, a point on the edge from a to b of a triangle
+    fn lerp(a: Self, b: Self, t: f32) -> Self
+    where
+        Self: Clone,
+    {
+        Self::barycentric_lerp(a.clone(), b, a, Vector3::new(1.0 - t, t, 0.0))
+    }
+}
+
+impl Interpolate for f32 {
+    fn barycentric_lerp(a: f32, b: f32, c: f32, weights: Vector3) -> f32 {
+        weights.dot(Vector3::new(a, b, c))
+    }
+}
+
+impl Interpolate for Vector2 {
+    fn barycentric_lerp(a: Vector2, b: Vector2, c: Vector2, weights: Vector3) -> Vector2 {
+        a * weights.x + b * weights.y + c * weights.z
+    }
+}
+
+impl Interpolate for Vector3 {
+    fn barycentric_lerp(a: Vector3, b: Vector3, c: Vector3, weights: Vector3) -> Vector3 {
+        a * weights.x + b * weights.y + c * weights.z
+    }
+}
+
+// Make sure that points are in counter-clockwise order
+pub fn edge_function(a: Vector3, b: Vector3, c: Vector3) -> f32 {
+    // Calculates vector representing the line from point A to C
+    let ac = c - a;
+
+    // Calculate the vector representing the triangle edge (A to B)
+    let ab = a - b;
+
+    // Calculating the normal/perpendicular vector of the AB side.
+    // Only x and y matter, otherwise the depth of the points would leak into the result
+    let ab_perp = Vector3::new(ab.y, -ab.x, 0.0);
+
+    // The dot product calculates how similar the directions of two vectors are
+    // If it is negative, then they are facing opposite directions
+    // If it is positive, then they are facing similar directions
+    // If the dot product between the normal and the AC vector are positive, then the vector is on the right side of the triangle
+    ac.dot(ab_perp)
+}
+
+// Maps normalized device coordinates onto a width by height viewport
+pub fn to_screen_coordinates(vec: Vector3, width: usize, height: usize) -> Vector3 {
+    (Matrix4::viewport(width, height) * vec.homogenous()).xyz()
+}
+
+pub fn get_normal(a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+    let ab = b - a;
+    let ac = c - a;
+    ab.cross(ac).normalize()
+}
+
+// Twice the signed area of a projected triangle
+// Negative when the points are in counter-clockwise order on screen (front facing)
+pub fn signed_area(t: &Triangle) -> f32 {
+    edge_function(*t.a.pos, *t.b.pos, *t.c.pos)
+}
+
+pub fn is_culled(t: &Triangle, mode: CullMode) -> bool {
+    let front_facing = signed_area(t) < 0.0;
+
+    match mode {
+        CullMode::None => false,
+        CullMode::Back => !front_facing,
+        CullMode::Front => front_facing,
+    }
+}
+
+// Only the cells inside the scissor (when there is one) that pass the stencil test are written
+pub fn rasterize_triangle<T: Rasterizable>(
+    t: &Triangle,
+    grid: &mut Grid<T>,
+    depth_buffer: &mut Grid<f32>,
+    shading: &Shading,
+    scissor: Option<Scissor>,
+    stencil: Option<&mut Stencil>,
+) {
+    let height = grid.height;
+    let mut band = Band {
+        rows: 0..height,
+        height,
+        grid,
+        depth_buffer,
+    };
+    rasterize_rows(t, &mut band, shading, scissor, stencil);
+}
+
+// Some rows of a screen, with a grid and depth buffer that only hold those rows
+pub(crate) struct Band<'a, T> {
+    // The first row of the range is row 0 of the buffers
+    pub rows: Range<usize>,
+    // Rows of the whole screen
+    pub height: usize,
+    pub grid: &'a mut Grid<T>,
+    pub depth_buffer: &'a mut Grid<f32>,
+}
+
+// Rasterizes the rows of a band. The scissor is in screen rows like the band's range, and the
+// stencil only holds the rows like the band's buffers.
+pub(crate) fn rasterize_rows<T: Rasterizable>(
+    t: &Triangle,
+    band: &mut Band<T>,
+    shading: &Shading,
+    scissor: Option<Scissor>,
+    mut stencil: Option<&mut Stencil>,
+) {
+    let Triangle { a, b, c } = t;
+    let (width, height, rows) = (band.grid.width, band.height, band.rows.clone());
+    let offset = rows.start;
+    let Band {
+        grid, depth_buffer, ..
+    } = band;
+
+    // Depth after the perspective divide is affine in screen space, so it's interpolated directly
+    for_each_pixel(t, width, height, rows, scissor, |x, y, weights| {
+        let depth = f32::barycentric_lerp(a.pos.z, b.pos.z, c.pos.z, weights);
+        let y = y - offset;
+
+        if let Some(stencil) = &stencil
+            && !stencil.passes(x, y)
+        {
+            return;
+        }
+
+        // Calculates the depth and uses it to determine whether current pixel is has lowest depth
+        if let Some(prev) = depth_buffer.get(x, y)
+            && depth >= *prev
+        {
+            return;
+        }
+
+        // Calculating light value
+        let value = T::shaded(intensity(t, weights, shading), shading);
+
+        depth_buffer.set(depth, x, y);
+        grid.set(value, x, y);
+        if let Some(stencil) = stencil.as_mut() {
+            stencil.write(x, y);
+        }
+    });
+}
+
+// Draws over whatever is already in the grid without a depth test, for the painter's algorithm
+pub fn paint_triangle<T: Rasterizable>(
+    t: &Triangle,
+    grid: &mut Grid<T>,
+    shading: &Shading,
+    scissor: Option<Scissor>,
+    mut stencil: Option<&mut Stencil>,
+) {
+    let (width, height) = (grid.width, grid.height);
+
+    for_each_pixel(t, width, height, 0..height, scissor, |x, y, weights| {
+        if let Some(stencil) = &stencil
+            && !stencil.passes(x, y)
+        {
+            return;
+        }
+
+        let value = T::shaded(intensity(t, weights, shading), shading);
+        grid.set(value, x, y);
+        if let Some(stencil) = stencil.as_mut() {
+            stencil.write(x, y);
+        }
+    });
+}
+
+// Calls f with the barycentric weights of every pixel inside the triangle that's in rows (and
+// in the scissor).
+// Edge functions are affine in x and y, so they're evaluated once at the corner of the
+// bounding box and then stepped by constant amounts from pixel to pixel. Rows before the
+// range are still stepped over, so every pixel gets the same weights whatever the range is.
+fn for_each_pixel(
+    t: &Triangle,
+    width: usize,
+    height: usize,
+    rows: Range<usize>,
+    scissor: Option<Scissor>,
+    mut f: impl FnMut(usize, usize, Vector3),
+) {
+    let Triangle { a, b, c } = t;
+
+    // Skip if any of the points are behind the camera
+    if a.pos.z < 0.0
+        || b.pos.z < 0.0
+        || c.pos.z < 0.0
+        || a.pos.z > 1.0
+        || b.pos.z > 1.0
+        || c.pos.z > 1.0
+    {
+        return;
+    }
+
+    let (a, b, c) = (*a.pos, *b.pos, *c.pos);
+
+    // Degenerate triangles cover no pixels
+    let area = edge_function(a, b, c);
+    if area == 0.0 {
+        return;
+    }
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = t.get_bounding_box(width, height);
+
+    // Stepping starts from the corner of the bounding box, so shrinking it to the scissor
+    // doesn't change the weights
+    if let Some(scissor) = scissor {
+        let Some((left, top, right, bottom)) = scissor.bounds(width, height) else {
+            return;
+        };
+
+        (min_x, min_y) = (min_x.max(left), min_y.max(top));
+        (max_x, max_y) = (max_x.min(right), max_y.min(bottom));
+        if min_x > max_x || min_y > max_y {
+            return;
+        }
+    }
+
+    // Edges opposite a, b and c, in the same order as the barycentric weights
+    let edges = |p: Vector3| {
+        Vector3::new(
+            edge_function(b, c, p),
+            edge_function(c, a, p),
+            edge_function(a, b, p),
+        )
+    };
+    let step_x = Vector3::new(b.y - c.y, c.y - a.y, a.y - b.y);
+    let step_y = Vector3::new(c.x - b.x, a.x - c.x, b.x - a.x);
+
+    let mut row = edges(Vector3::new(min_x as f32, min_y as f32, 0.0));
+
+    // Iterating through every pixel/point inside of triangle's bounding box
+    for y in min_y..=max_y.min(rows.end.saturating_sub(1)) {
+        if y < rows.start {
+            row += step_y;
+            continue;
+        }
+
+        let mut values = row;
+
+        for x in min_x..=max_x {
+            let weights = values / area;
+
+            // Accept both windings, culling is decided before rasterizing
+            if weights.x >= 0.0 && weights.y >= 0.0 && weights.z >= 0.0 {
+                f(x, y, weights);
+            }
+
+            values += step_x;
+        }
+
+        row += step_y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        Vector2,
+        color::{Cell, Color},
+        light::{DEFAULT_RAMP, Light, Lighting},
+        material::Material,
+        vertex::Vertex,
+    };
+
+    use super::*;
+
+    fn vertex(pos: &Vector3) -> Vertex<'_> {
+        Vertex {
+            pos,
+            tex_coord: None,
+            normal: None,
+            w: 1.0,
+        }
+    }
+
+    // Rasterizes with the default material lit by a single light
+    fn rasterize(t: &Triangle, grid: &mut Grid<char>, depth_buffer: &mut Grid<f32>, light: Light) {
+        let material = Material::new("default");
+        let lights = [light];
+        let shading = Shading {
+            lighting: Lighting::new(&lights, Vector3::new(0, 0, 1)),
+            material: &material,
+            ramp: DEFAULT_RAMP,
+            normal: Vector3::new(0, 0, 1),
+            world: None,
+        };
+        rasterize_triangle(t, grid, depth_buffer, &shading, None, None);
+    }
+
+    fn lit_vertex<'a>(pos: &'a Vector3, normal: &'a Vector3) -> Vertex<'a> {
+        Vertex {
+            pos,
+            tex_coord: None,
+            normal: Some(normal),
+            w: 1.0,
+        }
+    }
+
+    #[test]
+    fn depth_interpolation_test() {
+        let a = Vector3::new(0.0, 0.0, 0.2);
+        let b = Vector3::new(0.0, 20.0, 0.2);
+        let c = Vector3::new(20.0, 0.0, 0.2);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        let mut grid = Grid::new(' ', 30, 30);
+        let mut depth_buffer = Grid::new(f32::INFINITY, 30, 30);
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        rasterize(&t, &mut grid, &mut depth_buffer, light);
+
+        assert_abs_diff_eq!(*depth_buffer.get(2, 2).unwrap(), 0.2, epsilon = 1e-5);
+        assert_eq!(*depth_buffer.get(25, 25).unwrap(), f32::INFINITY);
+    }
+
+    #[test]
+    fn depth_test() {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        // Facing the light so it is shaded brightest
+        let near = [
+            Vector3::new(0.0, 0.0, 0.2),
+            Vector3::new(0.0, 20.0, 0.2),
+            Vector3::new(20.0, 0.0, 0.2),
+        ];
+        let near_normal = Vector3::new(0, 0, 1);
+
+        // Facing away from the light so it is shaded darkest
+        let far = [
+            Vector3::new(0.0, 0.0, 0.8),
+            Vector3::new(0.0, 20.0, 0.8),
+            Vector3::new(20.0, 0.0, 0.8),
+        ];
+        let far_normal = Vector3::new(0, 0, -1);
+
+        let near = Triangle {
+            a: lit_vertex(&near[0], &near_normal),
+            b: lit_vertex(&near[1], &near_normal),
+            c: lit_vertex(&near[2], &near_normal),
+        };
+        let far = Triangle {
+            a: lit_vertex(&far[0], &far_normal),
+            b: lit_vertex(&far[1], &far_normal),
+            c: lit_vertex(&far[2], &far_normal),
+        };
+
+        // The nearer triangle wins regardless of draw order
+        for near_first in [true, false] {
+            let mut grid = Grid::new(' ', 30, 30);
+            let mut depth_buffer = Grid::new(f32::INFINITY, 30, 30);
+
+            if near_first {
+                rasterize(&near, &mut grid, &mut depth_buffer, light);
+                rasterize(&far, &mut grid, &mut depth_buffer, light);
+            } else {
+                rasterize(&far, &mut grid, &mut depth_buffer, light);
+                rasterize(&near, &mut grid, &mut depth_buffer, light);
+            }
+
+            for (x, y) in [(1, 1), (5, 5), (10, 2), (2, 15)] {
+                assert_eq!(*grid.get(x, y).unwrap(), '@');
+                assert_abs_diff_eq!(*depth_buffer.get(x, y).unwrap(), 0.2, epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn cull_mode_test() {
+        // Counter-clockwise on screen
+        let ccw = [
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(0.0, 10.0, 0.5),
+            Vector3::new(10.0, 0.0, 0.5),
+        ];
+
+        // Clockwise on screen
+        let cw = [
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(10.0, 0.0, 0.5),
+            Vector3::new(0.0, 10.0, 0.5),
+        ];
+
+        let ccw = Triangle {
+            a: vertex(&ccw[0]),
+            b: vertex(&ccw[1]),
+            c: vertex(&ccw[2]),
+        };
+        let cw = Triangle {
+            a: vertex(&cw[0]),
+            b: vertex(&cw[1]),
+            c: vertex(&cw[2]),
+        };
+
+        assert!(!is_culled(&ccw, CullMode::None));
+        assert!(!is_culled(&cw, CullMode::None));
+
+        assert!(!is_culled(&ccw, CullMode::Back));
+        assert!(is_culled(&cw, CullMode::Back));
+
+        assert!(is_culled(&ccw, CullMode::Front));
+        assert!(!is_culled(&cw, CullMode::Front));
+    }
+
+    #[test]
+    fn rasterize_both_windings_test() {
+        let cw = [
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(10.0, 0.0, 0.5),
+            Vector3::new(0.0, 10.0, 0.5),
+        ];
+        let normal = Vector3::new(0, 0, 1);
+        let cw = Triangle {
+            a: lit_vertex(&cw[0], &normal),
+            b: lit_vertex(&cw[1], &normal),
+            c: lit_vertex(&cw[2], &normal),
+        };
+
+        let mut grid = Grid::new(' ', 20, 20);
+        let mut depth_buffer = Grid::new(f32::INFINITY, 20, 20);
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+        rasterize(&cw, &mut grid, &mut depth_buffer, light);
+
+        assert_eq!(*grid.get(2, 2).unwrap(), '@');
+        assert_abs_diff_eq!(*depth_buffer.get(2, 2).unwrap(), 0.5, epsilon = 1e-5);
+    }
+
+    // Ramp indices of row y after rasterizing both halves of a quad, with the normals at the
+    // corners given in the order top left, top right, bottom left, bottom right
+    fn shade_quad_row(normals: [[Vector3; 3]; 2], y: usize) -> Vec<usize> {
+        let corners = [
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(20.0, 0.0, 0.5),
+            Vector3::new(0.0, 10.0, 0.5),
+            Vector3::new(20.0, 10.0, 0.5),
+        ];
+
+        // Both triangles share the edge from the top right to the bottom left corner
+        let [top, bottom] = &normals;
+        let top = Triangle {
+            a: lit_vertex(&corners[0], &top[0]),
+            b: lit_vertex(&corners[1], &top[1]),
+            c: lit_vertex(&corners[2], &top[2]),
+        };
+        let bottom = Triangle {
+            a: lit_vertex(&corners[1], &bottom[0]),
+            b: lit_vertex(&corners[3], &bottom[1]),
+            c: lit_vertex(&corners[2], &bottom[2]),
+        };
+
+        let mut grid = Grid::new(' ', 21, 11);
+        let mut depth_buffer = Grid::new(f32::INFINITY, 21, 11);
+        let light = Light::new(Vector3::new(-1, 0, -1), 1.0);
+        rasterize(&top, &mut grid, &mut depth_buffer, light);
+        rasterize(&bottom, &mut grid, &mut depth_buffer, light);
+
+        (0..grid.width)
+            .map(|x| DEFAULT_RAMP.find(*grid.get(x, y).unwrap()).unwrap())
+            .collect()
+    }
+
+    fn largest_step(row: &[usize]) -> usize {
+        row.windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn smooth_shading_test() {
+        // Normals of a sphere patch that faces more towards the light on the right
+        let z = f32::sqrt(1.0 - 0.6 * 0.6 - 0.3 * 0.3);
+        let top_left = Vector3::new(-0.6, -0.3, z);
+        let top_right = Vector3::new(0.6, -0.3, z);
+        let bottom_left = Vector3::new(-0.6, 0.3, z);
+        let bottom_right = Vector3::new(0.6, 0.3, z);
+
+        let smooth = shade_quad_row(
+            [
+                [top_left, top_right, bottom_left],
+                [top_right, bottom_right, bottom_left],
+            ],
+            5,
+        );
+
+        // Flat shading gives every vertex of a triangle the triangle's averaged normal
+        let top = (top_left + top_right + bottom_left).normalize();
+        let bottom = (top_right + bottom_right + bottom_left).normalize();
+        let flat = shade_quad_row([[top; 3], [bottom; 3]], 5);
+
+        // Brightens gradually from left to right without a jump at the shared edge
+        assert!(smooth.first() < smooth.last());
+        assert!(smooth.is_sorted());
+        assert!(largest_step(&smooth) <= 1);
+        assert!(largest_step(&flat) > 1);
+    }
+
+    // Pixels covered by a triangle, found by the incremental rasterizer and by testing every
+    // pixel of the grid with Triangle::contains
+    fn covered_pixels(points: [Vector3; 3], size: usize) -> [Vec<(usize, usize)>; 2] {
+        let t = Triangle {
+            a: vertex(&points[0]),
+            b: vertex(&points[1]),
+            c: vertex(&points[2]),
+        };
+
+        let mut incremental = Vec::new();
+        for_each_pixel(&t, size, size, 0..size, None, |x, y, weights| {
+            let expected = t.barycentric(Vector2::new(x, y)).unwrap();
+            assert_abs_diff_eq!(weights, expected, epsilon = 1e-5);
+            incremental.push((x, y));
+        });
+
+        let mut brute_force = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                if t.contains(Vector2::new(x, y)) {
+                    brute_force.push((x, y));
+                }
+            }
+        }
+
+        [incremental, brute_force]
+    }
+
+    #[test]
+    fn incremental_rasterization_test() {
+        let triangles = [
+            // Both windings of a right triangle, with pixels right on its edges
+            [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)],
+            [(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)],
+            // Off the pixel grid
+            [(1.25, 2.5), (17.75, 6.5), (4.5, 15.25)],
+            // Long and thin
+            [(0.5, 1.0), (19.5, 3.0), (0.5, 1.5)],
+            // Partly outside of the grid
+            [(-5.0, 4.0), (12.0, -3.0), (30.0, 25.0)],
+        ];
+
+        for points in triangles {
+            let points = points.map(|(x, y)| Vector3::new(x, y, 0.5));
+            let [incremental, brute_force] = covered_pixels(points, 20);
+            assert!(!incremental.is_empty());
+            assert_eq!(incremental, brute_force);
+        }
+
+        // Steps like 0.1 and 1/3 aren't exact in f32, so the stepped values pick up rounding
+        // error over the hundreds of pixels of a large triangle
+        let third = 1.0 / 3.0;
+        let large = [
+            [(0.1, 0.3), (299.9, 100.0 * third), (150.0 * third, 299.7)],
+            [(299.3, 0.1), (0.7, 200.0 * third), (290.0 * third, 299.9)],
+        ];
+        for points in large {
+            let points = points.map(|(x, y)| Vector3::new(x, y, 0.5));
+            let [incremental, brute_force] = covered_pixels(points, 300);
+            assert!(incremental.len() > 10_000);
+            assert_eq!(incremental, brute_force);
+        }
+
+        // Degenerate triangles cover nothing
+        let line = [0.0, 5.0, 10.0].map(|x| Vector3::new(x, x, 0.5));
+        assert_eq!(covered_pixels(line, 20), [vec![], vec![]]);
+    }
+
+    #[test]
+    fn cell_types_test() {
+        let points = [
+            Vector3::new(1.25, 2.5, 0.5),
+            Vector3::new(17.75, 6.5, 0.5),
+            Vector3::new(4.5, 15.25, 0.5),
+        ];
+        let normal = Vector3::new(0, 0, 1);
+        let t = Triangle {
+            a: lit_vertex(&points[0], &normal),
+            b: lit_vertex(&points[1], &normal),
+            c: lit_vertex(&points[2], &normal),
+        };
+
+        let material = Material {
+            diffuse_color: Vector3::new(1.0, 0.5, 0.0),
+            ..Material::new("orange")
+        };
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let shading = Shading {
+            lighting: Lighting::new(&lights, Vector3::new(0, 0, 1)),
+            material: &material,
+            ramp: DEFAULT_RAMP,
+            normal: Vector3::new(0, 0, 1),
+            world: None,
+        };
+
+        let mut text = Grid::new(' ', 20, 20);
+        let mut depth_buffer = Grid::new(f32::INFINITY, 20, 20);
+        rasterize_triangle(&t, &mut text, &mut depth_buffer, &shading, None, None);
+
+        let mut mask = Grid::new(false, 20, 20);
+        let mut depth_buffer = Grid::new(f32::INFINITY, 20, 20);
+        rasterize_triangle(&t, &mut mask, &mut depth_buffer, &shading, None, None);
+
+        let mut cells = Grid::new(Cell::default(), 20, 20);
+        paint_triangle(&t, &mut cells, &shading, None, None);
+
+        // The same pixels are covered whatever the cells are
+        let covered: Vec<_> = text.iter().filter(|(_, _, ch)| **ch != ' ').collect();
+        assert!(!covered.is_empty());
+        assert!(mask.iter().all(|(x, y, set)| *set == (text[(x, y)] != ' ')));
+
+        // Colored cells get the same characters, in the material's color
+        for (x, y, cell) in cells.iter() {
+            assert_eq!(cell.ch, text[(x, y)]);
+            if cell.ch != ' ' {
+                assert_eq!(cell.fg, Color::Rgb(255, 128, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn interpolate_test() {
+        let centroid = Vector3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0);
+        let corners = [
+            Vector3::new(1, 0, 0),
+            Vector3::new(0, 1, 0),
+            Vector3::new(0, 0, 1),
+        ];
+
+        let depths = [0.2, 0.5, 0.8];
+        assert_abs_diff_eq!(f32::barycentric_lerp(0.2, 0.5, 0.8, centroid), 0.5);
+        for (weights, depth) in corners.iter().zip(depths) {
+            assert_abs_diff_eq!(f32::barycentric_lerp(0.2, 0.5, 0.8, *weights), depth);
+        }
+
+        let uvs = [
+            Vector2::new(0, 0),
+            Vector2::new(1, 0),
+            Vector2::new(0.5, 1.5),
+        ];
+        let [a, b, c] = uvs;
+        assert_abs_diff_eq!(
+            Vector2::barycentric_lerp(a, b, c, centroid),
+            Vector2::new(0.5, 0.5)
+        );
+        for (weights, uv) in corners.iter().zip(uvs) {
+            assert_abs_diff_eq!(Vector2::barycentric_lerp(a, b, c, *weights), uv);
+        }
+
+        let normals = [
+            Vector3::new(3, 0, 0),
+            Vector3::new(0, 3, 0),
+            Vector3::new(0, 0, 3),
+        ];
+        let [a, b, c] = normals;
+        assert_abs_diff_eq!(
+            Vector3::barycentric_lerp(a, b, c, centroid),
+            Vector3::new(1, 1, 1)
+        );
+        for (weights, normal) in corners.iter().zip(normals) {
+            assert_abs_diff_eq!(Vector3::barycentric_lerp(a, b, c, *weights), normal);
+        }
+
+        // Along an edge only its two ends count
+        assert_abs_diff_eq!(f32::lerp(0.2, 0.8, 0.25), 0.35);
+        assert_abs_diff_eq!(
+            <Vector3 as Interpolate>::lerp(a, c, 0.5),
+            Vector3::new(1.5, 0, 1.5)
+        );
+    }
+
+    #[test]
+    fn scissor_test() {
+        // Covers the whole scissor and more on every side
+        let points = [
+            Vector3::new(-4.0, -4.0, 0.5),
+            Vector3::new(30.0, 2.0, 0.5),
+            Vector3::new(2.0, 30.0, 0.5),
+        ];
+        let t = Triangle {
+            a: vertex(&points[0]),
+            b: vertex(&points[1]),
+            c: vertex(&points[2]),
+        };
+        let material = Material::new("default");
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let shading = Shading {
+            lighting: Lighting::new(&lights, Vector3::new(0, 0, 1)),
+            material: &material,
+            ramp: DEFAULT_RAMP,
+            normal: Vector3::new(0, 0, 1),
+            world: None,
+        };
+        let scissor = Scissor::new(3, 5, 6, 4);
+
+        let mut full = Grid::new(' ', 20, 20);
+        paint_triangle(&t, &mut full, &shading, None, None);
+
+        let mut depth_buffer = Grid::new(f32::INFINITY, 20, 20);
+        let mut scissored = Grid::new(' ', 20, 20);
+        rasterize_triangle(
+            &t,
+            &mut scissored,
+            &mut depth_buffer,
+            &shading,
+            Some(scissor),
+            None,
+        );
+
+        let mut painted = Grid::new(' ', 20, 20);
+        paint_triangle(&t, &mut painted, &shading, Some(scissor), None);
+
+        // Inside the scissor is the same as without one, everything else stays untouched
+        let inside = |x: usize, y: usize| (3..9).contains(&x) && (5..9).contains(&y);
+        for (x, y, ch) in full.iter() {
+            let expected = if inside(x, y) { *ch } else { ' ' };
+            assert_eq!(scissored[(x, y)], expected);
+            assert_eq!(painted[(x, y)], expected);
+            assert_eq!(depth_buffer[(x, y)].is_finite(), inside(x, y));
+        }
+        assert_eq!(
+            scissored.iter().filter(|(_, _, ch)| **ch != ' ').count(),
+            24
+        );
+
+        // Scissors outside the grid leave nothing to draw on
+        let mut grid = Grid::new(' ', 20, 20);
+        paint_triangle(
+            &t,
+            &mut grid,
+            &shading,
+            Some(Scissor::new(25, 0, 5, 5)),
+            None,
+        );
+        assert!(grid.iter().all(|(_, _, ch)| *ch == ' '));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    triangle::Triangle,
+    vector::{vector::Vector, vector3::Vector3},
+};
+
+// Determinants smaller than this are treated as the ray running along the surface
+const EPSILON: f32 = 1e-7;
+
+// Half line from origin going in dir, for picking and other ray queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    pub dir: Vector3,
+}
+
+// Every point p with normal · p = distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub distance: f32,
+}
+
+impl Plane {
+    // Plane through point facing normal
+    pub fn new(normal: Vector3, point: Vector3) -> Plane {
+        let normal = normal.normalize();
+        Plane {
+            normal,
+            distance: normal.dot(point),
+        }
+    }
+
+    // Positive on the side the normal points to
+    pub fn signed_distance(&self, point: Vector3) -> f32 {
+        self.normal.dot(point) - self.distance
+    }
+}
+
+impl Ray {
+    // dir is normalized, so the values intersections return are distances along the ray
+    pub fn new(origin: Vector3, dir: Vector3) -> Ray {
+        Ray {
+            origin,
+            dir: dir.normalize(),
+        }
+    }
+
+    // Point t along the ray
+    pub fn at(&self, t: f32) -> Vector3 {
+        self.origin + self.dir * t
+    }
+
+    // How far along the ray it meets the plane, None when it runs parallel to the plane or the
+    // plane is behind the origin
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denominator = plane.normal.dot(self.dir);
+        if denominator.abs() < EPSILON {
+            return None;
+        }
+
+        let t = -plane.signed_distance(self.origin) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+
+    // Möller–Trumbore: how far along the ray it hits the triangle (from either side, edges
+    // included), None when it misses, runs parallel to the triangle or the triangle is behind
+    // the origin
+    pub fn intersect(&self, tri: &Triangle) -> Option<f32> {
+        let (a, b, c) = (*tri.a.pos, *tri.b.pos, *tri.c.pos);
+        let ab = b - a;
+        let ac = c - a;
+
+        let p = self.dir.cross(ac);
+        let determinant = ab.dot(p);
+        if determinant.abs() < EPSILON {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let ao = self.origin - a;
+
+        // Barycentric weights of b and c at the hit point
+        let u = ao.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = ao.cross(ab);
+        let v = self.dir.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = ac.dot(q) * inverse;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::vertex::Vertex;
+
+    use super::*;
+
+    // Right triangle in the z = -5 plane with its corner at (0, 0, -5)
+    const POINTS: [Vector3; 3] = [
+        Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: -5.0,
+        },
+        Vector3 {
+            x: 2.0,
+            y: 0.0,
+            z: -5.0,
+        },
+        Vector3 {
+            x: 0.0,
+            y: 2.0,
+            z: -5.0,
+        },
+    ];
+
+    fn triangle() -> Triangle<'static> {
+        let vertex = |pos| Vertex {
+            pos,
+            tex_coord: None,
+            normal: None,
+            w: 1.0,
+        };
+
+        Triangle {
+            a: vertex(&POINTS[0]),
+            b: vertex(&POINTS[1]),
+            c: vertex(&POINTS[2]),
+        }
+    }
+
+    #[test]
+    fn hit_test() {
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 0), Vector3::new(0, 0, -1));
+        let t = ray.intersect(&triangle()).unwrap();
+        assert_abs_diff_eq!(t, 5.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(ray.at(t), Vector3::new(0.5, 0.5, -5), epsilon = 1e-6);
+
+        // The direction's length doesn't change the distance, and the back also gets hit
+        let ray = Ray::new(Vector3::new(0.5, 0.5, -8), Vector3::new(0, 0, 10));
+        assert_abs_diff_eq!(ray.intersect(&triangle()).unwrap(), 3.0, epsilon = 1e-6);
+
+        // At an angle, through a corner
+        let ray = Ray::new(Vector3::new(-3, 0, -1), Vector3::new(3, 0, -4));
+        assert_abs_diff_eq!(ray.intersect(&triangle()).unwrap(), 5.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn miss_test() {
+        // Parallel to the triangle
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 0), Vector3::new(1, 0, 0));
+        assert_eq!(ray.intersect(&triangle()), None);
+
+        // Pointing away from it
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 0), Vector3::new(0, 0, 1));
+        assert_eq!(ray.intersect(&triangle()), None);
+
+        // Past the long edge
+        let ray = Ray::new(Vector3::new(1.5, 1.5, 0), Vector3::new(0, 0, -1));
+        assert_eq!(ray.intersect(&triangle()), None);
+    }
+
+    #[test]
+    fn plane_test() {
+        let plane = Plane::new(Vector3::new(0, 2, 0), Vector3::new(4, 1, -3));
+        assert_abs_diff_eq!(plane.distance, 1.0);
+        assert_abs_diff_eq!(plane.signed_distance(Vector3::new(0, 3, 0)), 2.0);
+        assert_abs_diff_eq!(plane.signed_distance(Vector3::new(9, -1, 9)), -2.0);
+
+        let ray = Ray::new(Vector3::new(2, 5, 2), Vector3::new(0, -1, 0));
+        assert_abs_diff_eq!(ray.intersect_plane(&plane).unwrap(), 4.0);
+
+        // Parallel, and pointing away
+        let ray = Ray::new(Vector3::new(2, 5, 2), Vector3::new(1, 0, 0));
+        assert_eq!(ray.intersect_plane(&plane), None);
+        let ray = Ray::new(Vector3::new(2, 5, 2), Vector3::new(0, 1, 0));
+        assert_eq!(ray.intersect_plane(&plane), None);
+    }
+}
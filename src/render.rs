@@ -0,0 +1,904 @@
+#[cfg(feature = "rayon")]
+use std::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "rayon")]
+use crate::rasterizer::{Band, rasterize_rows};
+
+use crate::{
+    Grid,
+    clipping::{ClipVertex, clip_near},
+    draw::{draw_scissored_wireframe, draw_wireframe},
+    frustum::Frustum,
+    light::{DEFAULT_RAMP, Lighting, Shading},
+    material::Material,
+    matrix::{
+        matrix::Matrix, matrix3::Matrix3, matrix4::Matrix4, rotation::Rotation, scale::Scale,
+    },
+    model::Model,
+    rasterizer::{
+        CullMode, Rasterizable, Scissor, Stencil, StencilOp, StencilTest, get_normal, is_culled,
+        paint_triangle, rasterize_triangle, to_screen_coordinates,
+    },
+    triangle::Triangle,
+    vector::{vector::Vector, vector3::Vector3},
+    vertex::Vertex,
+};
+
+// How rendered frames look, set once instead of passing the same values to every call
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderConfig<T = char> {
+    // Cells that nothing is drawn on
+    pub background: T,
+    // Characters ordered from darkest to brightest
+    pub ramp: String,
+    pub cull_mode: CullMode,
+    // Only the cells inside it are drawn or cleared, the whole grid when None
+    pub scissor: Option<Scissor>,
+    // How triangles test and write the stencil buffer passed to the render functions.
+    // Wireframe edges are drawn over everything, they don't test or write the stencil just like
+    // they ignore the depth buffer
+    pub stencil_test: StencilTest,
+    pub stencil_op: StencilOp,
+}
+
+// The cell type's background, the default ramp, back faces culled, no scissor and the stencil
+// left alone
+impl<T: Rasterizable> Default for RenderConfig<T> {
+    fn default() -> Self {
+        RenderConfig {
+            background: T::BACKGROUND,
+            ramp: String::from(DEFAULT_RAMP),
+            cull_mode: CullMode::Back,
+            scissor: None,
+            stencil_test: StencilTest::Always,
+            stencil_op: StencilOp::Keep,
+        }
+    }
+}
+
+impl<T: Rasterizable> RenderConfig<T> {
+    // Sets every cell (in the scissor) back to the background
+    pub fn clear(&self, grid: &mut Grid<T>) {
+        match self.scissor {
+            Some(Scissor {
+                x,
+                y,
+                width,
+                height,
+            }) => grid.fill_rect(self.background.clone(), x, y, width, height),
+            None => grid.clear(self.background.clone()),
+        }
+    }
+
+    // Same settings for another cell type, with that type drawing on the given background
+    pub fn with_background<U>(&self, background: U) -> RenderConfig<U> {
+        RenderConfig {
+            background,
+            ramp: self.ramp.clone(),
+            cull_mode: self.cull_mode,
+            scissor: self.scissor,
+            stencil_test: self.stencil_test,
+            stencil_op: self.stencil_op,
+        }
+    }
+
+    fn stencil<'a>(&self, buffer: &'a mut Grid<bool>) -> Stencil<'a> {
+        Stencil {
+            buffer,
+            test: self.stencil_test,
+            op: self.stencil_op,
+        }
+    }
+
+    // Edges of wireframes, clipped to the scissor
+    fn draw_wireframe(&self, grid: &mut Grid<T>, t: &Triangle, value: T) {
+        match self.scissor {
+            Some(scissor) => draw_scissored_wireframe(grid, t, value, scissor),
+            None => draw_wireframe(grid, t, value),
+        }
+    }
+}
+
+// Buffers a frame is rendered into. The depth buffer and stencil are the same size as the grid,
+// and the stencil is tested and written the way the config says.
+pub struct RenderTarget<'a, T> {
+    pub grid: &'a mut Grid<T>,
+    pub depth_buffer: &'a mut Grid<f32>,
+    pub stencil: Option<&'a mut Grid<bool>>,
+}
+
+impl<'a, T> RenderTarget<'a, T> {
+    // Without a stencil
+    pub fn new(grid: &'a mut Grid<T>, depth_buffer: &'a mut Grid<f32>) -> Self {
+        RenderTarget {
+            grid,
+            depth_buffer,
+            stencil: None,
+        }
+    }
+
+    pub fn with_stencil(self, stencil: &'a mut Grid<bool>) -> Self {
+        RenderTarget {
+            stencil: Some(stencil),
+            ..self
+        }
+    }
+}
+
+// A projected triangle that owns its vertex attributes, with the normals in world space
+struct ScreenTriangle<'a> {
+    pos: [Vector3; 3],
+    vertices: [ClipVertex; 3],
+    material: &'a Material,
+    // World space face normal
+    normal: Vector3,
+}
+
+impl<'a> ScreenTriangle<'a> {
+    fn triangle(&self) -> Triangle<'_> {
+        let vertex = |i: usize| Vertex {
+            pos: &self.pos[i],
+            tex_coord: self.vertices[i].tex_coord.as_ref(),
+            normal: self.vertices[i].normal.as_ref(),
+            w: self.vertices[i].pos.w,
+        };
+
+        Triangle {
+            a: vertex(0),
+            b: vertex(1),
+            c: vertex(2),
+        }
+    }
+
+    // Lights the triangle's material, shaded with the characters of ramp
+    fn shading<'b>(&self, lighting: &Lighting<'b>, ramp: &'b str) -> Shading<'b>
+    where
+        'a: 'b,
+    {
+        Shading {
+            lighting: *lighting,
+            material: self.material,
+            ramp,
+            normal: self.normal,
+            world: Some(self.vertices.map(|vertex| vertex.world)),
+        }
+    }
+
+    // Average clip space w, which is the view space distance in front of the camera
+    // for perspective projections
+    fn depth(&self) -> f32 {
+        self.vertices.iter().map(|vertex| vertex.pos.w).sum::<f32>() / 3.0
+    }
+}
+
+// Transforms, clips and projects every triangle of a model onto a width by height viewport,
+// leaving out culled triangles
+fn project_model(
+    model: &Model,
+    view_projection: Matrix4,
+    cull_mode: CullMode,
+    width: usize,
+    height: usize,
+) -> Vec<ScreenTriangle<'_>> {
+    // Nothing of a model outside the frustum can end up on screen
+    if !Frustum::new(view_projection).contains_model(model) {
+        return Vec::new();
+    }
+
+    let transform = &model.transform;
+    let mut triangles = Vec::new();
+
+    // Model matrix
+    let model_matrix = transform.to_matrix();
+    let mvp = view_projection * model_matrix;
+
+    // Calculating world normal matrix (inverse transpose of the model matrix without translation)
+    let rotation = Matrix4::rotation(transform.yaw, transform.pitch, transform.roll);
+    let model_inverse = Matrix3::scale(1.0 / transform.scale) * rotation.cartesian().transpose();
+    let normal_matrix = model_inverse.transpose();
+
+    for (i, triangle) in model.triangles().enumerate() {
+        // Triangles with out of range indices can't be drawn and are left out
+        let Ok(Triangle { a, b, c }) = triangle else {
+            continue;
+        };
+
+        // Calculating the face normal (in object space), used for vertices without their own normal
+        let normal = get_normal(*a.pos, *b.pos, *c.pos);
+
+        // Converting normal vectors to world space
+        let normal = (normal_matrix * normal).normalize();
+        let world_normal = |vertex: ClipVertex| ClipVertex {
+            normal: Some(match vertex.normal {
+                Some(n) => (normal_matrix * n).normalize(),
+                None => normal,
+            }),
+            ..vertex
+        };
+
+        // Transform points into clip space using matrices
+        let clip_vertex = |vertex: &Vertex| {
+            let pos = vertex.pos.homogenous();
+            ClipVertex::new(mvp * pos, (model_matrix * pos).xyz(), vertex)
+        };
+        let clip_triangle = [clip_vertex(&a), clip_vertex(&b), clip_vertex(&c)];
+
+        // Clip against the near plane before the perspective divide
+        for vertices in clip_near(clip_triangle) {
+            // Convert points to screen coordinates, the normals are interpolated across the
+            // triangle when rasterizing for smooth shading
+            let triangle = ScreenTriangle {
+                pos: vertices
+                    .map(|vertex| to_screen_coordinates(vertex.pos.cartesian(), width, height)),
+                vertices: vertices.map(world_normal),
+                material: model.face_material(i),
+                normal,
+            };
+
+            if !is_culled(&triangle.triangle(), cull_mode) {
+                triangles.push(triangle);
+            }
+        }
+    }
+
+    triangles
+}
+
+// Transforms, clips, projects and rasterizes every triangle of a model into the target.
+// The view_projection matrix takes world space points to clip space.
+pub fn render_model<T: Rasterizable>(
+    model: &Model,
+    view_projection: Matrix4,
+    lighting: &Lighting,
+    config: &RenderConfig<T>,
+    wireframe: bool,
+    target: &mut RenderTarget<T>,
+) {
+    let RenderTarget {
+        grid,
+        depth_buffer,
+        stencil,
+    } = target;
+    let (width, height) = (grid.width, grid.height);
+    let mut stencil = stencil.as_deref_mut().map(|buffer| config.stencil(buffer));
+
+    for triangle in project_model(model, view_projection, config.cull_mode, width, height) {
+        let t = triangle.triangle();
+
+        if wireframe {
+            config.draw_wireframe(grid, &t, T::WIREFRAME);
+        } else {
+            let shading = triangle.shading(lighting, &config.ramp);
+            let stencil = stencil.as_mut();
+            rasterize_triangle(&t, grid, depth_buffer, &shading, config.scissor, stencil);
+        }
+    }
+}
+
+// Rows of the screen that one thread rasterizes at a time in render_model_parallel
+#[cfg(feature = "rayon")]
+const TILE_HEIGHT: usize = 16;
+
+// Same as render_model, but the screen is split into horizontal tiles that are rasterized on
+// separate threads into their own buffers and then copied back. Every tile draws the
+// triangles in model order, so the image is the same as render_model's no matter how many
+// threads there are. Wireframes are drawn on the calling thread.
+#[cfg(feature = "rayon")]
+pub fn render_model_parallel<T: Rasterizable + Send + Sync>(
+    model: &Model,
+    view_projection: Matrix4,
+    lighting: &Lighting,
+    config: &RenderConfig<T>,
+    wireframe: bool,
+    target: &mut RenderTarget<T>,
+) {
+    let (width, height) = (target.grid.width, target.grid.height);
+    if wireframe || width == 0 {
+        render_model(model, view_projection, lighting, config, wireframe, target);
+        return;
+    }
+
+    let RenderTarget {
+        grid,
+        depth_buffer,
+        stencil,
+    } = target;
+    let triangles = project_model(model, view_projection, config.cull_mode, width, height);
+
+    let starts: Vec<usize> = (0..height).step_by(TILE_HEIGHT).collect();
+    let stencil_buffer = stencil.as_deref();
+    let tiles: Vec<Tile<T>> = starts
+        .into_par_iter()
+        .map(|start| {
+            let rows = start..usize::min(start + TILE_HEIGHT, height);
+
+            // Tiles start out with what's already on screen, so earlier draws still occlude
+            let mut tile = copy_rows(grid, rows.clone());
+            let mut tile_depth = copy_rows(depth_buffer, rows.clone());
+            let mut tile_stencil = stencil_buffer.map(|buffer| copy_rows(buffer, rows.clone()));
+            let mut stencil = tile_stencil.as_mut().map(|buffer| config.stencil(buffer));
+
+            let mut band = Band {
+                rows,
+                height,
+                grid: &mut tile,
+                depth_buffer: &mut tile_depth,
+            };
+            for triangle in &triangles {
+                let t = triangle.triangle();
+                let shading = triangle.shading(lighting, &config.ramp);
+                rasterize_rows(&t, &mut band, &shading, config.scissor, stencil.as_mut());
+            }
+
+            (start, tile, tile_depth, tile_stencil)
+        })
+        .collect();
+
+    // Tiles don't overlap, so merging is just copying them back
+    for (start, tile, tile_depth, tile_stencil) in tiles {
+        paste_rows(grid, &tile, start);
+        paste_rows(depth_buffer, &tile_depth, start);
+        if let (Some(buffer), Some(tile_stencil)) = (stencil.as_deref_mut(), tile_stencil) {
+            paste_rows(buffer, &tile_stencil, start);
+        }
+    }
+}
+
+// First row, colors, depths and stencil of a tile of render_model_parallel
+#[cfg(feature = "rayon")]
+type Tile<T> = (usize, Grid<T>, Grid<f32>, Option<Grid<bool>>);
+
+#[cfg(feature = "rayon")]
+fn copy_rows<T: Clone>(grid: &Grid<T>, rows: Range<usize>) -> Grid<T> {
+    let mut tile = Grid::new(grid[(0, rows.start)].clone(), grid.width, rows.len());
+    for y in 0..rows.len() {
+        for x in 0..grid.width {
+            tile[(x, y)] = grid[(x, rows.start + y)].clone();
+        }
+    }
+    tile
+}
+
+#[cfg(feature = "rayon")]
+fn paste_rows<T: Clone>(grid: &mut Grid<T>, tile: &Grid<T>, start: usize) {
+    for (x, y, value) in tile.iter() {
+        grid.set(value.clone(), x, start + y);
+    }
+}
+
+// Same as render_model but without a depth buffer, triangles are sorted by their average
+// depth and painted back to front (the painter's algorithm). A single depth per triangle
+// can't order triangles that pass through each other or overlap in a cycle, so parts of
+// the farther one end up hidden, use render_model when that matters.
+pub fn render_model_sorted<T: Rasterizable>(
+    model: &Model,
+    view_projection: Matrix4,
+    lighting: &Lighting,
+    config: &RenderConfig<T>,
+    wireframe: bool,
+    grid: &mut Grid<T>,
+    stencil: Option<&mut Grid<bool>>,
+) {
+    let (width, height) = (grid.width, grid.height);
+    let mut stencil = stencil.map(|buffer| config.stencil(buffer));
+    let mut triangles = project_model(model, view_projection, config.cull_mode, width, height);
+    triangles.sort_by(|a, b| b.depth().total_cmp(&a.depth()));
+
+    for triangle in triangles {
+        let t = triangle.triangle();
+
+        if wireframe {
+            config.draw_wireframe(grid, &t, T::WIREFRAME);
+        } else {
+            let shading = triangle.shading(lighting, &config.ramp);
+            paint_triangle(&t, grid, &shading, config.scissor, stencil.as_mut());
+        }
+    }
+}
+
+// Shrinks a grid rendered at samples times the resolution on each axis, so every samples by
+// samples block becomes one cell (super-sampling anti-aliasing). Blocks are averaged by the
+// cell type, which makes cells that a triangle only partly covers dimmer than its inside and
+// softens jagged edges. Render into a Grid<Option<T>> to tell the blocks that nothing was
+// drawn on apart, those stay None.
+pub fn downsample<T: Rasterizable>(grid: &Grid<T>, samples: usize, ramp: &str) -> Grid<T> {
+    let samples = samples.max(1);
+    let mut image = Grid::new(T::BACKGROUND, grid.width / samples, grid.height / samples);
+    let mut block = Vec::with_capacity(samples * samples);
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            block.clear();
+            for sample_y in y * samples..(y + 1) * samples {
+                for sample_x in x * samples..(x + 1) * samples {
+                    block.push(grid[(sample_x, sample_y)].clone());
+                }
+            }
+
+            image[(x, y)] = T::average(&block, samples * samples, ramp);
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, str::FromStr};
+
+    use crate::{
+        HEIGHT, WIDTH,
+        light::{Light, ramp_char},
+        matrix::rotation::Angle,
+        vector::vector3::Vector3,
+    };
+
+    use super::*;
+
+    const CUBE: &str = "
+v 1.0 -1.0 -1.0
+v 1.0 -1.0 1.0
+v -1.0 -1.0 1.0
+v -1.0 -1.0 -1.0
+v 1.0 1.0 -1.0
+v 1.0 1.0 1.0
+v -1.0 1.0 1.0
+v -1.0 1.0 -1.0
+f 2 3 4
+f 8 7 6
+f 5 6 2
+f 6 7 3
+f 3 7 8
+f 1 4 8
+f 1 2 4
+f 5 8 6
+f 1 5 2
+f 2 6 3
+f 4 3 8
+f 5 1 8
+";
+
+    fn config<T: Rasterizable>(cull_mode: CullMode) -> RenderConfig<T> {
+        RenderConfig {
+            cull_mode,
+            ..RenderConfig::default()
+        }
+    }
+
+    fn load_cube() -> Model {
+        Model::from_str(CUBE).unwrap()
+    }
+
+    fn render(model: &Model) -> Grid<char> {
+        let aspect = (WIDTH as f32) / (HEIGHT as f32);
+        let projection = Matrix4::perspective(Angle::Degrees(60.0), 10.0, 0.05, aspect);
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        let mut grid = Grid::new(' ', WIDTH, HEIGHT);
+        let mut depth_buffer = Grid::new(f32::INFINITY, WIDTH, HEIGHT);
+        render_model(
+            model,
+            projection,
+            &Lighting::new(&[light], Vector3::new(0, 0, 1)),
+            &config(CullMode::None),
+            false,
+            &mut RenderTarget::new(&mut grid, &mut depth_buffer),
+        );
+        grid
+    }
+
+    const SIZE: usize = 81;
+
+    fn projection() -> Matrix4 {
+        Matrix4::perspective(Angle::Degrees(90.0), 10.0, 0.1, 1.0)
+    }
+
+    // Renders with both depth modes, the triangles facing +z are shaded brightest and the
+    // ones facing -z darkest
+    fn render_both(model: &Model) -> (Grid<char>, Grid<char>) {
+        let light = Light::new(Vector3::new(0, 0, -1), 1.0);
+
+        let mut buffered = Grid::new(' ', SIZE, SIZE);
+        let mut depth_buffer = Grid::new(f32::INFINITY, SIZE, SIZE);
+        render_model(
+            model,
+            projection(),
+            &Lighting::new(&[light], Vector3::new(0, 0, 1)),
+            &config(CullMode::None),
+            false,
+            &mut RenderTarget::new(&mut buffered, &mut depth_buffer),
+        );
+
+        let mut sorted = Grid::new(' ', SIZE, SIZE);
+        render_model_sorted(
+            model,
+            projection(),
+            &Lighting::new(&[light], Vector3::new(0, 0, 1)),
+            &config(CullMode::None),
+            false,
+            &mut sorted,
+            None,
+        );
+
+        (buffered, sorted)
+    }
+
+    // Cell that a world space point lands on
+    fn pixel(point: Vector3) -> (usize, usize) {
+        let ndc = (projection() * point.homogenous()).cartesian();
+        let screen = to_screen_coordinates(ndc, SIZE, SIZE);
+        (screen.x as usize, screen.y as usize)
+    }
+
+    fn filled_cells(grid: &Grid<char>) -> usize {
+        let mut count = 0;
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if *grid.get(x, y).unwrap() != ' ' {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn render_cube_test() {
+        let mut model = load_cube();
+        model.set_position(Vector3::new(0, 0, -5));
+        model.rotate_x(30.0);
+        model.rotate_y(30.0);
+
+        let grid = render(&model);
+        assert!(filled_cells(&grid) > 0);
+    }
+
+    #[test]
+    fn render_behind_camera_test() {
+        let mut model = load_cube();
+        model.set_position(Vector3::new(0, 0, 5));
+
+        let grid = render(&model);
+        assert_eq!(filled_cells(&grid), 0);
+    }
+
+    #[test]
+    fn painter_sorting_test() {
+        // The near triangle comes first, so drawing in file order would paint over it
+        let model = Model::from_str(
+            "v -0.5 -0.5 -2\nv 0.5 -0.5 -2\nv 0 0.5 -2\nv -3 -3 -4\nv 3 -3 -4\nv 0 3 -4\n\
+             vn 0 0 1\nvn 0 0 -1\nf 1//1 2//1 3//1\nf 4//2 5//2 6//2\n",
+        )
+        .unwrap();
+        let (buffered, sorted) = render_both(&model);
+
+        let near = pixel(Vector3::new(0, 0, -2));
+        let far = pixel(Vector3::new(2, -2, -4));
+        assert_eq!(sorted[near], '@');
+        assert_eq!(sorted[far], '.');
+
+        // Without any overlap in depth both modes agree
+        assert_eq!(buffered.to_string(), sorted.to_string());
+    }
+
+    #[test]
+    fn painter_interpenetration_test() {
+        // The second triangle cuts through the first one, it's in front on the left but
+        // farther away on average
+        let model = Model::from_str(
+            "v -3 -3 -3\nv 3 -3 -3\nv 0 3 -3\nv -1 -1 -2\nv -1 1 -2\nv 1.5 0 -5.5\n\
+             vn 0 0 1\nvn 0 0 -1\nf 1//1 2//1 3//1\nf 4//2 5//2 6//2\n",
+        )
+        .unwrap();
+        let (buffered, sorted) = render_both(&model);
+
+        let crossing = pixel(Vector3::new(-0.8, 0, -2.28));
+        assert_eq!(buffered[crossing], '.');
+
+        // Sorting whole triangles wrongly paints the first triangle over it
+        assert_eq!(sorted[crossing], '@');
+    }
+
+    #[test]
+    fn mask_test() {
+        let mut model = load_cube();
+        model.set_position(Vector3::new(0.5, 0, -5));
+        model.rotate_y(30.0);
+
+        let aspect = (WIDTH as f32) / (HEIGHT as f32);
+        let projection = Matrix4::perspective(Angle::Degrees(60.0), 10.0, 0.05, aspect);
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let lighting = Lighting::new(&lights, Vector3::new(0, 0, 1));
+
+        // The whole pipeline can render a coverage mask instead of text
+        let mut text = Grid::new(' ', WIDTH, HEIGHT);
+        let mut mask = Grid::new(false, WIDTH, HEIGHT);
+        for wireframe in [false, true] {
+            render_model_sorted(
+                &model,
+                projection,
+                &lighting,
+                &config(CullMode::Back),
+                wireframe,
+                &mut text,
+                None,
+            );
+            render_model_sorted(
+                &model,
+                projection,
+                &lighting,
+                &config(CullMode::Back),
+                wireframe,
+                &mut mask,
+                None,
+            );
+        }
+
+        assert!(mask.iter().any(|(_, _, set)| *set));
+        assert!(mask.iter().all(|(x, y, set)| *set == (text[(x, y)] != ' ')));
+    }
+
+    // Renders a cube with the stencil config into a fresh grid, with or without a depth buffer
+    fn render_stenciled(
+        model: &Model,
+        stencil: &mut Grid<bool>,
+        test: StencilTest,
+        op: StencilOp,
+        sorted: bool,
+        wireframe: bool,
+    ) -> Grid<char> {
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let lighting = Lighting::new(&lights, Vector3::new(0, 0, 1));
+        let config = RenderConfig {
+            stencil_test: test,
+            stencil_op: op,
+            ..RenderConfig::default()
+        };
+
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        let mut depth_buffer = Grid::new(f32::INFINITY, SIZE, SIZE);
+        let projection = projection();
+        if sorted {
+            render_model_sorted(
+                model,
+                projection,
+                &lighting,
+                &config,
+                wireframe,
+                &mut grid,
+                Some(stencil),
+            );
+        } else {
+            render_model(
+                model,
+                projection,
+                &lighting,
+                &config,
+                wireframe,
+                &mut RenderTarget::new(&mut grid, &mut depth_buffer).with_stencil(stencil),
+            );
+        }
+        grid
+    }
+
+    #[test]
+    fn stencil_test() {
+        let mut small = load_cube();
+        small.set_position(Vector3::new(0, 0, -6));
+
+        let mut large = load_cube();
+        large.set_position(Vector3::new(1, 0, -4));
+        large.rotate_y(30.0);
+
+        for sorted in [false, true] {
+            // The first pass marks the cells the small cube covers
+            let mut stencil = Grid::new(false, SIZE, SIZE);
+            let marked = render_stenciled(
+                &small,
+                &mut stencil,
+                StencilTest::Always,
+                StencilOp::Set,
+                sorted,
+                false,
+            );
+            assert!(stencil.iter().any(|(_, _, set)| *set));
+            let covered = |x: usize, y: usize| marked[(x, y)] != ' ';
+            assert!(stencil.iter().all(|(x, y, set)| *set == covered(x, y)));
+
+            let unmasked = render_stenciled(
+                &large,
+                &mut Grid::new(false, SIZE, SIZE),
+                StencilTest::Always,
+                StencilOp::Keep,
+                sorted,
+                false,
+            );
+            let inside = render_stenciled(
+                &large,
+                &mut stencil,
+                StencilTest::Set,
+                StencilOp::Keep,
+                sorted,
+                false,
+            );
+            let outside = render_stenciled(
+                &large,
+                &mut stencil,
+                StencilTest::Clear,
+                StencilOp::Keep,
+                sorted,
+                false,
+            );
+
+            // The second pass is split between the cells that were marked and the rest
+            for (x, y, ch) in unmasked.iter() {
+                let (expected_inside, expected_outside) = match stencil[(x, y)] {
+                    true => (*ch, ' '),
+                    false => (' ', *ch),
+                };
+                assert_eq!(inside[(x, y)], expected_inside);
+                assert_eq!(outside[(x, y)], expected_outside);
+            }
+            assert!(inside.iter().any(|(_, _, ch)| *ch != ' '));
+            assert!(outside.iter().any(|(_, _, ch)| *ch != ' '));
+        }
+    }
+
+    #[test]
+    fn wireframe_stencil_test() {
+        let mut model = load_cube();
+        model.set_position(Vector3::new(0, 0, -6));
+
+        for sorted in [false, true] {
+            // Edges are drawn and leave the stencil alone even where every cell fails the test
+            let mut stencil = Grid::new(false, SIZE, SIZE);
+            let grid = render_stenciled(
+                &model,
+                &mut stencil,
+                StencilTest::Set,
+                StencilOp::Set,
+                sorted,
+                true,
+            );
+            assert!(grid.iter().any(|(_, _, ch)| *ch != ' '));
+            assert!(stencil.iter().all(|(_, _, set)| !*set));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_test() {
+        let mut near = load_cube();
+        near.set_position(Vector3::new(0.5, 0, -5));
+        near.rotate_x(30.0);
+        near.rotate_y(30.0);
+
+        let mut far = load_cube();
+        far.set_position(Vector3::new(-1, 0.5, -6));
+        far.rotate_z(45.0);
+
+        let aspect = (WIDTH as f32) / (HEIGHT as f32);
+        let projection = Matrix4::perspective(Angle::Degrees(60.0), 10.0, 0.05, aspect);
+        let lights = [
+            Light::new(Vector3::new(0, 0, -1), 0.6),
+            Light::new(Vector3::new(1, -1, -1), 0.6),
+        ];
+
+        let render = |parallel: bool| {
+            let mut grid = Grid::new(' ', WIDTH, HEIGHT);
+            let mut depth_buffer = Grid::new(f32::INFINITY, WIDTH, HEIGHT);
+            for model in [&near, &far] {
+                let render = if parallel {
+                    render_model_parallel
+                } else {
+                    render_model
+                };
+                render(
+                    model,
+                    projection,
+                    &Lighting::new(&lights, Vector3::new(0, 0, 1)),
+                    &config(CullMode::Back),
+                    false,
+                    &mut RenderTarget::new(&mut grid, &mut depth_buffer),
+                );
+            }
+            (grid.to_string(), depth_buffer.map(|depth| depth.to_bits()))
+        };
+
+        let (serial, serial_depth) = render(false);
+        assert!(serial.chars().any(|ch| !ch.is_whitespace()));
+
+        // Same image and depths whatever the number of threads
+        for threads in [1, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let (parallel, parallel_depth) = pool.install(|| render(true));
+            assert_eq!(parallel, serial);
+            assert!(parallel_depth.iter().eq(serial_depth.iter()));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_stencil_test() {
+        let mut model = load_cube();
+        model.set_position(Vector3::new(0.5, 0, -5));
+        model.rotate_x(30.0);
+
+        let aspect = (WIDTH as f32) / (HEIGHT as f32);
+        let projection = Matrix4::perspective(Angle::Degrees(60.0), 10.0, 0.05, aspect);
+        let lights = [Light::new(Vector3::new(0, 0, -1), 1.0)];
+        let lighting = Lighting::new(&lights, Vector3::new(0, 0, 1));
+
+        // Every cell is only drawn once, by the first triangle that reaches it
+        let config = RenderConfig {
+            stencil_test: StencilTest::Clear,
+            stencil_op: StencilOp::Set,
+            ..config(CullMode::None)
+        };
+
+        let render = |parallel: bool| {
+            let mut grid = Grid::new(' ', WIDTH, HEIGHT);
+            let mut depth_buffer = Grid::new(f32::INFINITY, WIDTH, HEIGHT);
+            let mut stencil = Grid::new(false, WIDTH, HEIGHT);
+            let render = if parallel {
+                render_model_parallel
+            } else {
+                render_model
+            };
+            render(
+                &model,
+                projection,
+                &lighting,
+                &config,
+                false,
+                &mut RenderTarget::new(&mut grid, &mut depth_buffer).with_stencil(&mut stencil),
+            );
+            let stencil: Vec<bool> = stencil.iter().map(|(_, _, set)| *set).collect();
+            (grid.to_string(), stencil)
+        };
+
+        let (serial, serial_stencil) = render(false);
+        assert!(serial_stencil.contains(&true));
+        assert_eq!(render(true), (serial, serial_stencil));
+    }
+
+    #[test]
+    fn downsample_test() {
+        // Lit everywhere below the diagonal of a 16 by 16 sample grid
+        let mut samples = Grid::new(None, 16, 16);
+        for y in 0..16 {
+            for x in 0..=y {
+                samples[(x, y)] = Some('@');
+            }
+        }
+
+        let image = downsample(&samples, 2, DEFAULT_RAMP);
+        assert_eq!((image.width, image.height), (8, 8));
+        for y in 0..8 {
+            for x in 0..8 {
+                // On the diagonal 3 of the 4 samples are lit
+                let expected = match x.cmp(&y) {
+                    Ordering::Less => Some('@'),
+                    Ordering::Equal => Some(ramp_char(0.75, DEFAULT_RAMP)),
+                    Ordering::Greater => None,
+                };
+                assert_eq!(image[(x, y)], expected);
+            }
+        }
+
+        // Without super-sampling the grid stays the same
+        let image = downsample(&samples, 1, DEFAULT_RAMP);
+        assert!(image.iter().all(|(x, y, cell)| *cell == samples[(x, y)]));
+
+        // Covered samples count even when they're dark
+        let mut samples = Grid::new(None, 2, 2);
+        samples[(0, 0)] = Some('.');
+        assert_eq!(downsample(&samples, 2, DEFAULT_RAMP)[(0, 0)], Some('.'));
+    }
+}
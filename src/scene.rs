@@ -0,0 +1,344 @@
+use crate::{
+    Grid,
+    camera::Camera,
+    fog::Fog,
+    light::{Light, Lighting},
+    model::Model,
+    rasterizer::Rasterizable,
+    render::{RenderConfig, RenderTarget, downsample, render_model},
+};
+
+// Everything drawn in a frame, seen through a single camera
+pub struct Scene<T = char> {
+    pub models: Vec<Model>,
+    pub lights: Vec<Light>,
+    pub camera: Camera,
+    pub config: RenderConfig<T>,
+    pub wireframe: bool,
+    pub fog: Option<Fog>,
+    // Samples per cell along each axis for super-sampling, 1 turns it off
+    pub samples: usize,
+}
+
+impl<T: Rasterizable> Scene<T> {
+    // Empty scene with the default render config and no fog
+    pub fn new(camera: Camera) -> Scene<T> {
+        Scene {
+            models: Vec::new(),
+            lights: Vec::new(),
+            camera,
+            config: RenderConfig::default(),
+            wireframe: false,
+            fog: None,
+            samples: 1,
+        }
+    }
+
+    // Clears the grid to the background and renders every model into it, sharing one depth
+    // buffer so models in front hide the ones behind them
+    pub fn render(&self, grid: &mut Grid<T>) {
+        self.config.clear(grid);
+
+        if self.samples <= 1 {
+            self.render_models(grid, &self.config);
+            return;
+        }
+
+        // Render at a higher resolution and average it down. Samples keep track of whether
+        // anything was drawn on them, so cells that nothing covers keep the background.
+        let mut samples: Grid<Option<T>> =
+            Grid::new(None, grid.width * self.samples, grid.height * self.samples);
+        let scissor = self.config.scissor;
+        let config = RenderConfig {
+            scissor: scissor.map(|scissor| scissor.scale(self.samples)),
+            ..self.config.with_background(None)
+        };
+        self.render_models(&mut samples, &config);
+
+        let image = downsample(&samples, self.samples, &self.config.ramp);
+        for (x, y, cell) in image.iter() {
+            if let Some(value) = cell {
+                grid[(x, y)] = value.clone();
+            }
+        }
+    }
+
+    fn render_models<U: Rasterizable>(&self, grid: &mut Grid<U>, config: &RenderConfig<U>) {
+        let mut depth_buffer = Grid::new(f32::INFINITY, grid.width, grid.height);
+        let view_projection = self.camera.view_projection();
+        let lighting = Lighting {
+            lights: &self.lights,
+            view: -self.camera.forward(),
+            eye: Some(self.camera.position),
+            fog: self.fog,
+        };
+
+        let mut target = RenderTarget::new(grid, &mut depth_buffer);
+        for model in &self.models {
+            render_model(
+                model,
+                view_projection,
+                &lighting,
+                config,
+                self.wireframe,
+                &mut target,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::{Cell, Color},
+        fog::FogMode,
+        light::{DEFAULT_RAMP, ramp_char},
+        matrix::rotation::Angle,
+        rasterizer::Scissor,
+        vector::vector3::Vector3,
+    };
+
+    use super::*;
+
+    const SIZE: usize = 41;
+
+    // Unit square facing +z
+    const QUAD: &str = "
+v -0.5 -0.5 0
+v 0.5 -0.5 0
+v 0.5 0.5 0
+v -0.5 0.5 0
+vn 0 0 1
+f 1//1 2//1 3//1
+f 1//1 3//1 4//1
+";
+
+    fn quad(position: Vector3) -> Model {
+        let mut model: Model = QUAD.parse().unwrap();
+        model.set_position(position);
+        model
+    }
+
+    fn scene<T: Rasterizable>() -> Scene<T> {
+        let camera = Camera::new(Angle::Degrees(90.0), 1.0, 0.1, 10.0);
+        let mut scene = Scene::new(camera);
+        scene.models.push(quad(Vector3::new(-1.5, 0, -4)));
+        scene.models.push(quad(Vector3::new(1.5, 0, -4)));
+        scene.lights.push(Light::new(Vector3::new(0, 0, -1), 1.0));
+        scene
+    }
+
+    // Columns of the filled cells left and right of the middle of the grid
+    fn filled_columns(grid: &Grid<char>) -> (Vec<usize>, Vec<usize>) {
+        let columns = grid
+            .iter()
+            .filter(|(_, _, ch)| **ch != ' ')
+            .map(|(x, _, _)| x);
+        columns.partition(|x| *x < SIZE / 2)
+    }
+
+    #[test]
+    fn two_models_test() {
+        let scene = scene();
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        let (left, right) = filled_columns(&grid);
+        assert!(!left.is_empty());
+        assert!(!right.is_empty());
+
+        // Both quads face the light head on
+        assert!(grid.iter().all(|(_, _, ch)| *ch == ' ' || *ch == '@'));
+    }
+
+    #[test]
+    fn camera_test() {
+        let mut scene = scene();
+        let mut before = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut before);
+
+        // Stepping right moves everything left on screen, until the left quad is out of view
+        scene.camera.move_right(4.0);
+        let mut after = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut after);
+
+        let (_, right_before) = filled_columns(&before);
+        let (left_after, right_after) = filled_columns(&after);
+        assert!(!left_after.is_empty());
+        assert!(right_after.is_empty());
+        assert!(left_after.iter().max() < right_before.iter().min());
+    }
+
+    #[test]
+    fn unlit_test() {
+        let mut scene = scene();
+        scene.lights.clear();
+
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        // Still rasterized, but with the darkest character of the ramp
+        assert!(grid.iter().any(|(_, _, ch)| *ch == '.'));
+    }
+
+    #[test]
+    fn background_test() {
+        let mut scene = scene();
+        let mut blank = Grid::new('?', SIZE, SIZE);
+        scene.render(&mut blank);
+
+        scene.config.background = '.';
+        let mut dotted = Grid::new('?', SIZE, SIZE);
+        scene.render(&mut dotted);
+
+        // Only the cells that nothing was drawn on are different
+        let mut untouched = 0;
+        for (x, y, ch) in blank.iter() {
+            if *ch == ' ' {
+                assert_eq!(dotted[(x, y)], '.');
+                untouched += 1;
+            } else {
+                assert_eq!(dotted[(x, y)], *ch);
+            }
+        }
+        assert!(untouched > 0 && untouched < SIZE * SIZE);
+    }
+
+    #[test]
+    fn ramp_test() {
+        let mut scene = scene();
+        scene.config.ramp = String::from("ox");
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        // The quads face the light, so they get the brightest character of the ramp
+        assert!(grid.iter().any(|(_, _, ch)| *ch == 'x'));
+        assert!(grid.iter().all(|(_, _, ch)| *ch == ' ' || *ch == 'x'));
+    }
+
+    #[test]
+    fn scissor_test() {
+        // Two views of the same scene side by side, each one only drawing into its half
+        let mut scene = scene();
+        let half = SIZE / 2;
+        let mut grid = Grid::new('?', SIZE, SIZE);
+
+        scene.config.scissor = Some(Scissor::new(0, 0, half, SIZE));
+        scene.render(&mut grid);
+        assert!(grid.iter().all(|(x, _, ch)| (x < half) != (*ch == '?')));
+
+        scene.config.scissor = Some(Scissor::new(half, 0, SIZE - half, SIZE));
+        scene.camera.move_right(1.5);
+        scene.wireframe = true;
+        scene.render(&mut grid);
+
+        let (left, right) = filled_columns(&grid);
+        assert!(!left.is_empty() && !right.is_empty());
+        assert!(grid.iter().all(|(_, _, ch)| *ch != '?'));
+        assert!(grid.iter().any(|(x, _, ch)| x >= half && *ch == '#'));
+        assert!(grid.iter().all(|(x, _, ch)| x >= half || *ch != '#'));
+    }
+
+    #[test]
+    fn super_sampled_scissor_test() {
+        let mut scene = scene();
+        scene.samples = 2;
+        scene.config.scissor = Some(Scissor::new(0, 0, SIZE / 2, SIZE));
+
+        let mut grid = Grid::new('?', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        // The left quad is drawn and the right half isn't even cleared
+        assert!(grid.iter().all(|(x, _, ch)| (x < SIZE / 2) != (*ch == '?')));
+        assert!(grid.iter().any(|(_, _, ch)| *ch == '@'));
+    }
+
+    #[test]
+    fn fog_test() {
+        // Both quads are 4 away, halfway into the fog
+        let mut scene = scene();
+        scene.fog = Some(Fog::new(3.0, 5.0, FogMode::Darken));
+
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        let half = ramp_char(0.5, DEFAULT_RAMP);
+        assert!(grid.iter().any(|(_, _, ch)| *ch == half));
+        assert!(grid.iter().all(|(_, _, ch)| *ch == ' ' || *ch == half));
+    }
+
+    #[test]
+    fn super_sampling_test() {
+        let mut scene = scene();
+        scene.samples = 2;
+
+        let mut grid = Grid::new(' ', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        // Both quads are still there, and only their edges are dimmer than the inside
+        let (left, right) = filled_columns(&grid);
+        assert!(!left.is_empty());
+        assert!(!right.is_empty());
+
+        let dimmed: Vec<_> = grid
+            .iter()
+            .filter(|(_, _, ch)| !matches!(**ch, ' ' | '@'))
+            .collect();
+        assert!(!dimmed.is_empty());
+        for (x, y, _) in dimmed {
+            // Cells past the border of the grid count as not lit
+            let neighbors = [
+                x.checked_sub(1).map(|x| (x, y)),
+                Some((x + 1, y)),
+                y.checked_sub(1).map(|y| (x, y)),
+                Some((x, y + 1)),
+            ];
+            assert!(
+                neighbors
+                    .iter()
+                    .any(|cell| cell.and_then(|(x, y)| grid.get(x, y)) != Some(&'@'))
+            );
+        }
+    }
+
+    #[test]
+    fn super_sampling_background_test() {
+        // Everything is drawn as a space, which still counts as covered
+        let mut scene = scene();
+        scene.samples = 2;
+        scene.config.background = '?';
+        scene.config.ramp = String::from(" ");
+
+        let mut grid = Grid::new('.', SIZE, SIZE);
+        scene.render(&mut grid);
+
+        // Both quads are drawn, and everything else keeps the background
+        let drawn: Vec<usize> = grid
+            .iter()
+            .filter(|(_, _, ch)| **ch == ' ')
+            .map(|(x, _, _)| x)
+            .collect();
+        assert!(drawn.iter().any(|x| *x < SIZE / 2));
+        assert!(drawn.iter().any(|x| *x >= SIZE / 2));
+        assert!(grid.iter().all(|(_, _, ch)| matches!(*ch, ' ' | '?')));
+    }
+
+    #[test]
+    fn colored_test() {
+        // Any cell type renders the same characters as text, with and without super-sampling
+        for samples in [1, 2] {
+            let mut text_scene = scene();
+            text_scene.samples = samples;
+            let mut text = Grid::new(' ', SIZE, SIZE);
+            text_scene.render(&mut text);
+
+            let mut cell_scene = scene();
+            cell_scene.samples = samples;
+            let mut cells = Grid::new(Cell::default(), SIZE, SIZE);
+            cell_scene.render(&mut cells);
+
+            assert!(cells.iter().all(|(x, y, cell)| cell.ch == text[(x, y)]));
+            assert!(cells.iter().any(|(_, _, cell)| cell.fg != Color::Default));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::io;
+
+use crossterm::{cursor, execute, style, terminal};
+
+use crate::{HEIGHT, WIDTH};
+
+// Raw mode with a hidden cursor on a cleared screen, everything is restored when dropped
+// so the shell is usable again even if rendering panics
+pub struct RawTerminal;
+
+impl RawTerminal {
+    pub fn enter() -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        let guard = RawTerminal;
+        execute!(
+            io::stdout(),
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            style::ResetColor,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+            cursor::Show
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Size of the terminal in characters, falls back to WIDTH x HEIGHT when it can't be
+// queried (e.g. when the output isn't a terminal)
+pub fn terminal_size() -> (usize, usize) {
+    viewport_size(terminal::size().ok())
+}
+
+fn viewport_size(size: Option<(u16, u16)>) -> (usize, usize) {
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => (width as usize, height as usize),
+        _ => (WIDTH, HEIGHT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_size_test() {
+        assert_eq!(viewport_size(Some((80, 24))), (80, 24));
+    }
+
+    #[test]
+    fn fallback_test() {
+        assert_eq!(viewport_size(None), (WIDTH, HEIGHT));
+        assert_eq!(viewport_size(Some((0, 0))), (WIDTH, HEIGHT));
+        assert_eq!(viewport_size(Some((80, 0))), (WIDTH, HEIGHT));
+    }
+}
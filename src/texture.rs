@@ -0,0 +1,375 @@
+use crate::{Grid, Vector2, color::Color, rasterizer::Interpolate, vector::vector::Vector};
+
+// How texture coordinates outside of [0, 1] are brought back onto the texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WrapMode {
+    // Sticks to the nearest edge
+    Clamp,
+    // Tiles the texture, 1.0 is the same as 0.0
+    Repeat,
+    // Tiles the texture, flipping every other copy
+    Mirror,
+}
+
+impl WrapMode {
+    fn wrap(&self, t: f32) -> f32 {
+        match self {
+            WrapMode::Clamp => t.clamp(0.0, 1.0),
+            WrapMode::Repeat => t.rem_euclid(1.0),
+            WrapMode::Mirror => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 { 2.0 - t } else { t }
+            }
+        }
+    }
+}
+
+// Image stored top row first, while v = 0 is the bottom row like texture coordinates in obj files
+pub struct Texture<T> {
+    pub pixels: Grid<T>,
+    // Smaller copies of pixels for sampling minified textures, each one half the size of the one
+    // before down to 1x1. Empty until generate_mips is called.
+    pub mips: Vec<Grid<T>>,
+}
+
+impl<T: Clone> Texture<T> {
+    pub fn new(pixels: Grid<T>) -> Texture<T> {
+        Texture {
+            pixels,
+            mips: Vec::new(),
+        }
+    }
+
+    // Level 0 is the full size texture and the mips come after it
+    pub fn level(&self, level: usize) -> &Grid<T> {
+        match level {
+            0 => &self.pixels,
+            _ => &self.mips[level - 1],
+        }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.mips.len() + 1
+    }
+
+    // Level of detail for a point whose texture coordinates change by duv_dx and duv_dy from
+    // one cell to the next (along x and y of the screen). 0 when every cell covers at most one
+    // texel, and 1 more every time the number of texels a cell covers doubles.
+    pub fn lod(&self, duv_dx: Vector2, duv_dy: Vector2) -> f32 {
+        let size = Vector2::new(self.pixels.width as f32, self.pixels.height as f32);
+        let texels = f32::max((duv_dx * size).length(), (duv_dy * size).length());
+        texels.log2().max(0.0)
+    }
+
+    // Checkerboard of size by size tiles for checking texture mapping, with a in the top left
+    // tile and b in the ones next to it
+    pub fn checker(width: usize, height: usize, size: usize, a: T, b: T) -> Texture<T> {
+        let size = size.max(1);
+        let pixels = Grid::from_fn(width, height, |x, y| match (x / size + y / size) % 2 {
+            0 => a.clone(),
+            _ => b.clone(),
+        });
+        Texture::new(pixels)
+    }
+
+    // Nearest neighbor sampling, panics if the texture is empty
+    pub fn sample(&self, uv: Vector2, wrap: WrapMode) -> T {
+        let x = Texture::<T>::texel(wrap.wrap(uv.x), self.pixels.width);
+        let y = Texture::<T>::texel(wrap.wrap(uv.y), self.pixels.height);
+        self.pixels[(x, self.pixels.height - 1 - y)].clone()
+    }
+
+    // Texel containing t in [0, 1], where 1.0 belongs to the last texel
+    fn texel(t: f32, size: usize) -> usize {
+        usize::min((t * size as f32) as usize, size - 1)
+    }
+}
+
+impl Texture<Color> {
+    // Every texel's texture coordinates as a color, u in red and v in green, for seeing how
+    // coordinates are mapped and interpolated across surfaces
+    pub fn uv_gradient(width: usize, height: usize) -> Texture<Color> {
+        let channel = |t: f32| (t * 255.0).round() as u8;
+        let pixels = Grid::from_fn(width, height, |x, y| {
+            // Texel centers, with v going up from the bottom row
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = 1.0 - (y as f32 + 0.5) / height as f32;
+            Color::Rgb(channel(u), channel(v), 0)
+        });
+        Texture::new(pixels)
+    }
+}
+
+impl<T: Interpolate + Clone> Texture<T> {
+    // Replaces the mips with ones built from pixels, every texel is the average of the 2x2
+    // block of texels it covers in the level above (the last row or column is repeated for
+    // odd sizes)
+    pub fn generate_mips(&mut self) {
+        self.mips.clear();
+
+        // Nothing to average, and no smaller level to make
+        let (mut width, mut height) = (self.pixels.width, self.pixels.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        while width > 1 || height > 1 {
+            let previous = self.level(self.levels() - 1);
+            let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+
+            let mip = Grid::from_fn(next_width, next_height, |x, y| {
+                let texel = |dx: usize, dy: usize| {
+                    let x = usize::min(2 * x + dx, width - 1);
+                    let y = usize::min(2 * y + dy, height - 1);
+                    previous[(x, y)].clone()
+                };
+
+                let top = T::lerp(texel(0, 0), texel(1, 0), 0.5);
+                let bottom = T::lerp(texel(0, 1), texel(1, 1), 0.5);
+                T::lerp(top, bottom, 0.5)
+            });
+
+            self.mips.push(mip);
+            (width, height) = (next_width, next_height);
+        }
+    }
+
+    // Blends the 4 texels nearest to uv on one level, panics if the level doesn't exist
+    pub fn sample_bilinear(&self, uv: Vector2, level: usize, wrap: WrapMode) -> T {
+        let pixels = self.level(level);
+        let (width, height) = (pixels.width, pixels.height);
+
+        // Texel centers are at half texel offsets
+        let x = wrap.wrap(uv.x) * width as f32 - 0.5;
+        let y = wrap.wrap(uv.y) * height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        // Repeating wraps the neighbors around, the other modes stop at the edge texel
+        let index = |i: i64, size: usize| match wrap {
+            WrapMode::Repeat => i.rem_euclid(size as i64) as usize,
+            _ => i.clamp(0, size as i64 - 1) as usize,
+        };
+        let texel = |dx: i64, dy: i64| {
+            let x = index(x0 as i64 + dx, width);
+            let y = index(y0 as i64 + dy, height);
+            pixels[(x, height - 1 - y)].clone()
+        };
+
+        let bottom = T::lerp(texel(0, 0), texel(1, 0), tx);
+        let top = T::lerp(texel(0, 1), texel(1, 1), tx);
+        T::lerp(bottom, top, ty)
+    }
+
+    // Bilinear samples of the two levels around lod, blended by how far lod is between them.
+    // Levels past the last mip use the last one, so call generate_mips first.
+    pub fn sample_trilinear(&self, uv: Vector2, lod: f32, wrap: WrapMode) -> T {
+        let last = self.levels() - 1;
+        let lod = lod.clamp(0.0, last as f32);
+        let level = lod.floor() as usize;
+
+        let near = self.sample_bilinear(uv, level, wrap);
+        if level == last {
+            return near;
+        }
+
+        let far = self.sample_bilinear(uv, level + 1, wrap);
+        T::lerp(near, far, lod - level as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    // a b
+    // c d
+    fn texture() -> Texture<char> {
+        let mut pixels = Grid::new(' ', 2, 2);
+        pixels.set('a', 0, 0);
+        pixels.set('b', 1, 0);
+        pixels.set('c', 0, 1);
+        pixels.set('d', 1, 1);
+        Texture::new(pixels)
+    }
+
+    #[test]
+    fn corners_test() {
+        let texture = texture();
+
+        for wrap in [WrapMode::Clamp, WrapMode::Mirror] {
+            let sample = |u: f32, v: f32| texture.sample(Vector2::new(u, v), wrap);
+            assert_eq!(sample(0.0, 0.0), 'c');
+            assert_eq!(sample(1.0, 0.0), 'd');
+            assert_eq!(sample(0.0, 1.0), 'a');
+            assert_eq!(sample(1.0, 1.0), 'b');
+        }
+
+        // Repeating wraps 1.0 back around to 0.0
+        let sample = |u: f32, v: f32| texture.sample(Vector2::new(u, v), WrapMode::Repeat);
+        assert_eq!(sample(0.0, 0.0), 'c');
+        assert_eq!(sample(1.0, 1.0), 'c');
+        assert_eq!(sample(0.99, 0.99), 'b');
+    }
+
+    #[test]
+    fn clamp_test() {
+        let texture = texture();
+        let sample = |u: f32, v: f32| texture.sample(Vector2::new(u, v), WrapMode::Clamp);
+        assert_eq!(sample(1.5, -0.5), 'd');
+        assert_eq!(sample(-3.0, 0.75), 'a');
+        assert_eq!(sample(0.75, 20.0), 'b');
+    }
+
+    #[test]
+    fn repeat_test() {
+        let texture = texture();
+        let sample = |u: f32, v: f32| texture.sample(Vector2::new(u, v), WrapMode::Repeat);
+        assert_eq!(sample(1.25, 0.25), 'c');
+        assert_eq!(sample(-0.25, 0.25), 'd');
+        assert_eq!(sample(3.25, -1.25), 'a');
+    }
+
+    #[test]
+    fn mirror_test() {
+        let texture = texture();
+        let sample = |u: f32, v: f32| texture.sample(Vector2::new(u, v), WrapMode::Mirror);
+        assert_eq!(sample(1.25, 0.25), 'd');
+        assert_eq!(sample(-0.25, 0.25), 'c');
+        assert_eq!(sample(2.75, -1.25), 'b');
+    }
+
+    #[test]
+    fn mip_sizes_test() {
+        let mut texture = Texture::new(Grid::new(0.0, 8, 4));
+        texture.generate_mips();
+        let sizes: Vec<_> = (0..texture.levels())
+            .map(|i| (texture.level(i).width, texture.level(i).height))
+            .collect();
+        assert_eq!(sizes, vec![(8, 4), (4, 2), (2, 1), (1, 1)]);
+
+        // Odd sizes round down, and the shorter side stays at 1
+        let mut texture = Texture::new(Grid::new(0.0, 5, 3));
+        texture.generate_mips();
+        assert_eq!(texture.levels(), 3);
+        assert_eq!((texture.mips[0].width, texture.mips[0].height), (2, 1));
+
+        // Generating again starts over instead of adding more levels
+        texture.generate_mips();
+        assert_eq!(texture.levels(), 3);
+        let mut texture = Texture::new(Grid::new(0.0, 1, 1));
+        texture.generate_mips();
+        assert_eq!(texture.levels(), 1);
+
+        // Empty textures only have the base level
+        for (width, height) in [(0, 4), (4, 0), (0, 0)] {
+            let mut texture = Texture::new(Grid::new(0.0, width, height));
+            texture.generate_mips();
+            assert_eq!(texture.levels(), 1);
+        }
+    }
+
+    #[test]
+    fn mip_average_test() {
+        // 1 3
+        // 5 7
+        let mut pixels = Grid::new(0.0, 2, 2);
+        pixels.set(1.0, 0, 0);
+        pixels.set(3.0, 1, 0);
+        pixels.set(5.0, 0, 1);
+        pixels.set(7.0, 1, 1);
+        let mut texture = Texture::new(pixels);
+        texture.generate_mips();
+        assert_eq!(texture.mips[0][(0, 0)], 4.0);
+
+        // Bilinear at a texel center is that texel, and between all four is their average
+        let center =
+            |u: f32, v: f32| texture.sample_bilinear(Vector2::new(u, v), 0, WrapMode::Clamp);
+        assert_abs_diff_eq!(center(0.25, 0.75), 1.0);
+        assert_abs_diff_eq!(center(0.75, 0.25), 7.0);
+        assert_abs_diff_eq!(center(0.5, 0.5), 4.0);
+
+        // Halfway between the levels
+        let uv = Vector2::new(0.25, 0.25);
+        let sample = texture.sample_trilinear(uv, 0.5, WrapMode::Clamp);
+        assert_abs_diff_eq!(sample, (5.0 + 4.0) / 2.0);
+    }
+
+    #[test]
+    fn flat_color_test() {
+        let color = Color::Rgb(200, 120, 40);
+        let mut texture = Texture::new(Grid::new(color, 16, 8));
+        texture.generate_mips();
+
+        for lod in [0.0, 0.3, 1.0, 2.5, 3.75, 4.0, 10.0] {
+            for uv in [
+                Vector2::new(0, 0),
+                Vector2::new(0.3, 0.9),
+                Vector2::new(1, 1),
+            ] {
+                for wrap in [WrapMode::Clamp, WrapMode::Repeat, WrapMode::Mirror] {
+                    assert_eq!(texture.sample_trilinear(uv, lod, wrap), color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lod_test() {
+        let texture = Texture::new(Grid::new(0.0, 64, 32));
+
+        // One texel per cell or less is full detail, then every doubling is the next level
+        let lod = |du: f32, dv: f32| texture.lod(Vector2::new(du, 0), Vector2::new(0, dv));
+        assert_abs_diff_eq!(lod(1.0 / 64.0, 1.0 / 32.0), 0.0);
+        assert_abs_diff_eq!(lod(1.0 / 256.0, 0.0), 0.0);
+        assert_abs_diff_eq!(lod(4.0 / 64.0, 1.0 / 32.0), 2.0);
+        assert_abs_diff_eq!(lod(1.0 / 64.0, 8.0 / 32.0), 3.0);
+    }
+
+    #[test]
+    fn checker_test() {
+        let texture = Texture::checker(8, 6, 2, '#', '.');
+        for (y, row) in texture.pixels.to_string().lines().enumerate() {
+            let expected = if (y / 2) % 2 == 0 {
+                "##..##.."
+            } else {
+                "..##..##"
+            };
+            assert_eq!(row, expected);
+        }
+
+        // Tiles change exactly at multiples of the size
+        let texture = Texture::checker(10, 1, 3, 0, 1);
+        let row: Vec<i32> = texture.pixels.iter().map(|(_, _, value)| *value).collect();
+        assert_eq!(row, [0, 0, 0, 1, 1, 1, 0, 0, 0, 1]);
+
+        // A size of 0 is treated as single texel tiles
+        let texture = Texture::checker(3, 2, 0, 'a', 'b');
+        assert_eq!(texture.pixels.to_string(), "aba\nbab\n");
+    }
+
+    #[test]
+    fn uv_gradient_test() {
+        let texture = Texture::uv_gradient(16, 8);
+
+        // Red grows with u along every row, green with v up every column
+        let increasing = |values: Vec<u8>| values.windows(2).all(|pair| pair[0] < pair[1]);
+        let rgb = |x: usize, y: usize| texture.pixels[(x, y)].to_rgb();
+        for y in 0..8 {
+            assert!(increasing((0..16).map(|x| rgb(x, y).0).collect()));
+        }
+        for x in 0..16 {
+            assert!(increasing((0..8).rev().map(|y| rgb(x, y).1).collect()));
+        }
+
+        // Sampling gives back roughly the coordinates it was sampled at
+        for (u, v) in [(0.1, 0.9), (0.5, 0.5), (0.8, 0.2)] {
+            let color = texture.sample(Vector2::new(u, v), WrapMode::Clamp);
+            let (r, g, _) = color.to_rgb();
+            assert_abs_diff_eq!(r as f32 / 255.0, u, epsilon = 0.04);
+            assert_abs_diff_eq!(g as f32 / 255.0, v, epsilon = 0.07);
+        }
+    }
+}
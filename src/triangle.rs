@@ -1,4 +1,9 @@
-use crate::{HEIGHT, WIDTH, vector::vector3::Vector3, vertex::Vertex};
+use crate::{
+    Vector2,
+    rasterizer::{Interpolate, edge_function},
+    vector::{vector::Vector, vector3::Vector3},
+    vertex::Vertex,
+};
 
 #[derive(Debug)]
 pub struct Triangle<'a> {
@@ -8,22 +13,248 @@ pub struct Triangle<'a> {
 }
 
 impl<'a> Triangle<'a> {
-    pub fn get_bounding_box(&self) -> (usize, usize, usize, usize) {
-        let a = self.a.pos;
-        let b = self.b.pos;
-        let c = self.c.pos;
+    // Bounding box in screen coordinates, clamped to a width by height viewport
+    pub fn get_bounding_box(&self, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        let (a, b, c) = (*self.a.pos, *self.b.pos, *self.c.pos);
 
         // Calculate triangle's bounding box
-        let min_x = f32::min(a.x, f32::min(b.x, c.x));
-        let min_y = f32::min(a.y, f32::min(b.y, c.y));
-        let max_x = f32::max(a.x, f32::max(b.x, c.x));
-        let max_y = f32::max(a.y, f32::max(b.y, c.y));
-        
-        let min_x = usize::clamp(min_x as usize, 0, WIDTH);
-        let min_y = usize::clamp(min_y as usize, 0, HEIGHT);
-        let max_x = usize::clamp(max_x as usize, 0, WIDTH);
-        let max_y = usize::clamp(max_y as usize, 0, HEIGHT);
+        let min = a.min(b).min(c);
+        let max = a.max(b).max(c);
+
+        // Inclusive bounds, so the last column and row are width - 1 and height - 1
+        let last_x = width.saturating_sub(1);
+        let last_y = height.saturating_sub(1);
+
+        let min_x = usize::clamp(min.x as usize, 0, last_x);
+        let min_y = usize::clamp(min.y as usize, 0, last_y);
+        let max_x = usize::clamp(max.x as usize, 0, last_x);
+        let max_y = usize::clamp(max.y as usize, 0, last_y);
 
         (min_x, min_y, max_x, max_y)
     }
+
+    // Barycentric weights of p (each weight belongs to the vertex opposite its edge), or None
+    // for degenerate triangles. Dividing by the signed area makes the weights independent of
+    // the winding, so they're all non-negative exactly when p is inside either way around.
+    pub fn barycentric(&self, p: Vector2) -> Option<Vector3> {
+        let (a, b, c) = (*self.a.pos, *self.b.pos, *self.c.pos);
+        let p = Vector3::new(p.x, p.y, 0.0);
+
+        let abc = edge_function(a, b, c);
+        if abc == 0.0 {
+            return None;
+        }
+
+        let abp = edge_function(a, b, p);
+        let bcp = edge_function(b, c, p);
+        let cap = edge_function(c, a, p);
+
+        Some(Vector3::new(bcp / abc, cap / abc, abp / abc))
+    }
+
+    // Points on an edge or a vertex count as inside
+    pub fn contains(&self, p: Vector2) -> bool {
+        self.barycentric(p)
+            .is_some_and(|weights| weights.x >= 0.0 && weights.y >= 0.0 && weights.z >= 0.0)
+    }
+
+    // Blends the vertex texture coordinates, None unless every vertex has one
+    pub fn tex_coord(&self, weights: Vector3) -> Option<Vector2> {
+        let (a, b, c) = (self.a.tex_coord?, self.b.tex_coord?, self.c.tex_coord?);
+        Some(Vector2::barycentric_lerp(*a, *b, *c, weights))
+    }
+
+    // Screen space weights are affine in 1/w rather than across the triangle itself, so they
+    // are divided by each w and renormalized (the same as interpolating u/w, v/w and 1/w).
+    // Attributes like normals and texture coordinates are blended with these for every fragment.
+    pub fn perspective_weights(&self, weights: Vector3) -> Vector3 {
+        let weights = weights * (1.0 / Vector3::new(self.a.w, self.b.w, self.c.w));
+        weights / (weights.x + weights.y + weights.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        matrix::{matrix4::Matrix4, rotation::Angle},
+        rasterizer::to_screen_coordinates,
+    };
+
+    use super::*;
+
+    fn vertex(pos: &Vector3) -> Vertex<'_> {
+        Vertex {
+            pos,
+            tex_coord: None,
+            normal: None,
+            w: 1.0,
+        }
+    }
+
+    #[test]
+    fn bounding_box_viewport_test() {
+        let a = Vector3::new(2.5, 1.0, 0.5);
+        let b = Vector3::new(50.0, 8.0, 0.5);
+        let c = Vector3::new(10.0, 60.0, 0.5);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        assert_eq!(t.get_bounding_box(100, 100), (2, 1, 50, 60));
+        assert_eq!(t.get_bounding_box(40, 20), (2, 1, 39, 19));
+    }
+
+    #[test]
+    fn bounding_box_off_screen_test() {
+        // Hangs off the top left corner
+        let a = Vector3::new(-10.0, -5.0, 0.5);
+        let b = Vector3::new(8.0, -5.0, 0.5);
+        let c = Vector3::new(-10.0, 6.0, 0.5);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        assert_eq!(t.get_bounding_box(30, 30), (0, 0, 8, 6));
+        assert_eq!(t.get_bounding_box(5, 3), (0, 0, 4, 2));
+
+        // Exactly on the right and bottom edges is still inside the grid
+        let a = Vector3::new(0.0, 0.0, 0.5);
+        let b = Vector3::new(30.0, 0.0, 0.5);
+        let c = Vector3::new(0.0, 30.0, 0.5);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        let (_, _, max_x, max_y) = t.get_bounding_box(30, 30);
+        assert_eq!((max_x, max_y), (29, 29));
+    }
+
+    #[test]
+    fn contains_test() {
+        // Counter-clockwise on screen
+        let a = Vector3::new(0.0, 0.0, 0.2);
+        let b = Vector3::new(0.0, 10.0, 0.5);
+        let c = Vector3::new(10.0, 0.0, 0.8);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        assert!(t.contains(Vector2::new(2, 2)));
+        assert!(t.contains(Vector2::new(5, 0)));
+        assert!(t.contains(Vector2::new(5, 5)));
+        assert!(t.contains(Vector2::new(0, 10)));
+        assert!(!t.contains(Vector2::new(6, 6)));
+        assert!(!t.contains(Vector2::new(-1, 2)));
+
+        // The opposite winding covers the same points
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&c),
+            c: vertex(&b),
+        };
+        assert!(t.contains(Vector2::new(2, 2)));
+        assert!(!t.contains(Vector2::new(6, 6)));
+    }
+
+    #[test]
+    fn barycentric_test() {
+        let a = Vector3::new(0.0, 0.0, 0.5);
+        let b = Vector3::new(0.0, 10.0, 0.5);
+        let c = Vector3::new(10.0, 0.0, 0.5);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: vertex(&c),
+        };
+
+        assert_abs_diff_eq!(
+            t.barycentric(Vector2::new(0, 0)).unwrap(),
+            Vector3::new(1, 0, 0)
+        );
+        assert_abs_diff_eq!(
+            t.barycentric(Vector2::new(0, 10)).unwrap(),
+            Vector3::new(0, 1, 0)
+        );
+        assert_abs_diff_eq!(
+            t.barycentric(Vector2::new(5, 0)).unwrap(),
+            Vector3::new(0.5, 0, 0.5)
+        );
+
+        // Degenerate triangles have no weights
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&a),
+            c: vertex(&c),
+        };
+        assert_eq!(t.barycentric(Vector2::new(1, 0)), None);
+    }
+
+    #[test]
+    fn perspective_weights_test() {
+        // Left half of a floor quad running from 1 to 3 units in front of the camera
+        let projection = Matrix4::perspective(Angle::Degrees(90.0), 10.0, 0.1, 1.0);
+        let corners = [
+            Vector3::new(-1, -1, -1),
+            Vector3::new(1, -1, -1),
+            Vector3::new(-1, -1, -3),
+        ];
+        let uvs = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)];
+
+        let clip = corners.map(|corner| projection * corner.homogenous());
+        let screen = clip.map(|pos| to_screen_coordinates(pos.cartesian(), 100, 100));
+        let vertex = |i: usize| Vertex {
+            pos: &screen[i],
+            tex_coord: Some(&uvs[i]),
+            normal: None,
+            w: clip[i].w,
+        };
+        let t = Triangle {
+            a: vertex(0),
+            b: vertex(1),
+            c: vertex(2),
+        };
+
+        // Halfway up the screen along the left edge is only 1.5 units away, a quarter of the
+        // way along the edge in 3D
+        let midpoint = (screen[0] + screen[2]) / 2.0;
+        let weights = t.barycentric(Vector2::new(midpoint.x, midpoint.y)).unwrap();
+
+        let affine = t.tex_coord(weights).unwrap();
+        let perspective = t.tex_coord(t.perspective_weights(weights)).unwrap();
+        assert_abs_diff_eq!(affine, Vector2::new(0, 0.5), epsilon = 1e-5);
+        assert_abs_diff_eq!(perspective, Vector2::new(0, 0.25), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn unprojected_tex_coord_test() {
+        let a = Vector3::new(0.0, 0.0, 0.5);
+        let b = Vector3::new(0.0, 10.0, 0.5);
+        let c = Vector3::new(10.0, 0.0, 0.5);
+        let uv = Vector2::new(1, 1);
+        let t = Triangle {
+            a: vertex(&a),
+            b: vertex(&b),
+            c: Vertex {
+                tex_coord: Some(&uv),
+                ..vertex(&c)
+            },
+        };
+
+        // Every vertex needs a texture coordinate
+        let weights = Vector3::new(0.2, 0.3, 0.5);
+        assert_eq!(t.tex_coord(weights), None);
+
+        // With w = 1 both ways of interpolating agree
+        assert_eq!(t.perspective_weights(weights), weights);
+    }
 }
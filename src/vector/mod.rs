@@ -1,3 +1,5 @@
+pub(crate) mod simd;
+#[allow(clippy::module_inception)]
 pub mod vector;
 pub mod vector2;
 pub mod vector3;
@@ -0,0 +1,99 @@
+// Four lane f32 arithmetic behind Vector4 and Matrix4. With the simd feature the lanes are
+// packed into a single register with wide, otherwise every lane is computed on its own.
+#[cfg(feature = "simd")]
+pub(crate) use packed::*;
+#[cfg(not(feature = "simd"))]
+pub(crate) use scalar::*;
+
+pub(crate) type Lanes = [f32; 4];
+
+#[cfg_attr(feature = "simd", allow(dead_code))]
+mod scalar {
+    use std::array;
+
+    use super::Lanes;
+
+    pub(crate) fn add(a: Lanes, b: Lanes) -> Lanes {
+        array::from_fn(|i| a[i] + b[i])
+    }
+
+    pub(crate) fn sub(a: Lanes, b: Lanes) -> Lanes {
+        array::from_fn(|i| a[i] - b[i])
+    }
+
+    pub(crate) fn mul(a: Lanes, b: Lanes) -> Lanes {
+        array::from_fn(|i| a[i] * b[i])
+    }
+
+    pub(crate) fn scale(a: Lanes, scalar: f32) -> Lanes {
+        array::from_fn(|i| a[i] * scalar)
+    }
+
+    pub(crate) fn dot(a: Lanes, b: Lanes) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+    }
+
+    // Sum of the columns weighted by the entries of v, which is a column-major matrix times v
+    pub(crate) fn transform(cols: [Lanes; 4], v: Lanes) -> Lanes {
+        let weighted = |i: usize| scale(cols[i], v[i]);
+        add(add(add(weighted(0), weighted(1)), weighted(2)), weighted(3))
+    }
+}
+
+#[cfg(feature = "simd")]
+mod packed {
+    use wide::f32x4;
+
+    use super::Lanes;
+
+    pub(crate) fn add(a: Lanes, b: Lanes) -> Lanes {
+        (f32x4::new(a) + f32x4::new(b)).to_array()
+    }
+
+    pub(crate) fn sub(a: Lanes, b: Lanes) -> Lanes {
+        (f32x4::new(a) - f32x4::new(b)).to_array()
+    }
+
+    pub(crate) fn mul(a: Lanes, b: Lanes) -> Lanes {
+        (f32x4::new(a) * f32x4::new(b)).to_array()
+    }
+
+    pub(crate) fn scale(a: Lanes, scalar: f32) -> Lanes {
+        (f32x4::new(a) * f32x4::new([scalar; 4])).to_array()
+    }
+
+    pub(crate) fn dot(a: Lanes, b: Lanes) -> f32 {
+        (f32x4::new(a) * f32x4::new(b)).reduce_add()
+    }
+
+    pub(crate) fn transform(cols: [Lanes; 4], v: Lanes) -> Lanes {
+        let weighted = |i: usize| f32x4::new(cols[i]) * f32x4::new([v[i]; 4]);
+        (weighted(0) + weighted(1) + weighted(2) + weighted(3)).to_array()
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    const A: Lanes = [1.5, -2.25, 1e6, 3.0e-4];
+    const B: Lanes = [-0.75, 8.0, 2.5e-3, 7.0];
+
+    #[test]
+    fn matches_scalar_test() {
+        // Lane-wise operations round the same way in both paths
+        assert_eq!(packed::add(A, B), scalar::add(A, B));
+        assert_eq!(packed::sub(A, B), scalar::sub(A, B));
+        assert_eq!(packed::mul(A, B), scalar::mul(A, B));
+        assert_eq!(packed::scale(A, -3.5), scalar::scale(A, -3.5));
+
+        // Sums can be added up in a different order
+        assert_abs_diff_eq!(packed::dot(A, B), scalar::dot(A, B), epsilon = 1e-3);
+
+        let cols = [A, B, [0.0, 1.0, 2.0, 3.0], [4.0, -5.0, 6.0, -7.0]];
+        let v = [2.0, -1.0, 0.5, 1.0];
+        assert_eq!(packed::transform(cols, v), scalar::transform(cols, v));
+    }
+}
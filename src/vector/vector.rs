@@ -1,9 +1,165 @@
-use std::{ops::Mul, process::Output};
+use std::{
+    fmt,
+    ops::{Add, Mul, Sub},
+};
 
-pub trait Vector {
-    type VectorType;
+use approx::AbsDiffEq;
 
+// Provided methods are built on the required ones and the arithmetic operators
+pub trait Vector: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self> {
     fn length(&self) -> f32;
-    fn normalize(&self) -> Self::VectorType;
-    fn dot(&self, other: Self::VectorType) -> f32;
+    fn normalize(&self) -> Self;
+
+    // None when the vector is too short to have a direction, where normalize would give NaN
+    fn try_normalize(&self) -> Option<Self> {
+        if self.length() <= f32::EPSILON {
+            None
+        } else {
+            Some(self.normalize())
+        }
+    }
+    fn dot(&self, other: Self) -> f32;
+
+    fn distance(&self, other: Self) -> f32 {
+        (*self - other).length()
+    }
+
+    // Squared distance avoids the square root when only comparing distances
+    fn distance_squared(&self, other: Self) -> f32 {
+        let difference = *self - other;
+        difference.dot(difference)
+    }
+
+    // Reflects the vector about a plane with the given normal (assumed to be unit length)
+    fn reflect(&self, normal: Self) -> Self {
+        *self - normal * (2.0 * self.dot(normal))
+    }
+
+    // Refracts a unit vector through a surface with the given unit normal, where eta is the
+    // ratio of refractive indices. Returns None on total internal reflection.
+    fn refract(&self, normal: Self, eta: f32) -> Option<Self> {
+        let cos = self.dot(normal);
+        let k = 1.0 - eta * eta * (1.0 - cos * cos);
+        if k < 0.0 {
+            return None;
+        }
+
+        Some(*self * eta - normal * (eta * cos + f32::sqrt(k)))
+    }
+
+    // Linear interpolation, returns exactly self at t = 0 and other at t = 1
+    fn lerp(&self, other: Self, t: f32) -> Self {
+        *self * (1.0 - t) + other * t
+    }
+
+    // Component-wise clamping
+    fn clamp(&self, min: Self, max: Self) -> Self;
+    fn clamp_scalar(&self, min: f32, max: f32) -> Self;
+
+    // Applies f to every component
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self;
+
+    // Applies f to each pair of matching components
+    fn zip_map(&self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self;
+
+    // Component-wise minimum and maximum
+    fn min(&self, other: Self) -> Self {
+        self.zip_map(other, f32::min)
+    }
+
+    fn max(&self, other: Self) -> Self {
+        self.zip_map(other, f32::max)
+    }
+
+    // Component-wise absolute value and rounding
+    fn abs(&self) -> Self {
+        self.map(f32::abs)
+    }
+
+    fn floor(&self) -> Self {
+        self.map(f32::floor)
+    }
+
+    fn ceil(&self) -> Self {
+        self.map(f32::ceil)
+    }
+
+    fn round(&self) -> Self {
+        self.map(f32::round)
+    }
+
+    // Component of the vector parallel to other (zero if other has no length)
+    fn project_onto(&self, other: Self) -> Self {
+        let length_squared = other.dot(other);
+        if length_squared == 0.0 {
+            return other * 0.0;
+        }
+
+        other * (self.dot(other) / length_squared)
+    }
+
+    // Component of the vector perpendicular to other
+    fn reject_from(&self, other: Self) -> Self {
+        *self - self.project_onto(other)
+    }
+
+    // Angle in radians between the two vectors (zero if either has no length)
+    fn angle_between(&self, other: Self) -> f32 {
+        let lengths = self.length() * other.length();
+        if lengths == 0.0 {
+            return 0.0;
+        }
+
+        // Rounding can push the cosine slightly outside of [-1, 1]
+        (self.dot(other) / lengths).clamp(-1.0, 1.0).acos()
+    }
+
+    // Component-wise comparison with an absolute tolerance, unlike == which needs exact bits
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool
+    where
+        Self: AbsDiffEq<Epsilon = f32>,
+    {
+        self.abs_diff_eq(other, epsilon)
+    }
 }
+
+// Writes the components as (x, y, ...), passing the formatter's precision through to each one
+pub(crate) fn write_components(f: &mut fmt::Formatter<'_>, components: &[f32]) -> fmt::Result {
+    write!(f, "(")?;
+    for (i, component) in components.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt::Display::fmt(component, f)?;
+    }
+    write!(f, ")")
+}
+
+// IntoIterator over the components and Sum, which are the same for every vector type
+macro_rules! impl_iterators {
+    ($vector:ident, $n:literal) => {
+        impl IntoIterator for $vector {
+            type Item = f32;
+            type IntoIter = std::array::IntoIter<f32, $n>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.components()
+            }
+        }
+
+        // Component-wise, the sum of nothing is the zero vector
+        impl std::iter::Sum for $vector {
+            fn sum<I: Iterator<Item = $vector>>(iter: I) -> $vector {
+                iter.fold($vector::default(), |total, v| total + v)
+            }
+        }
+
+        impl<'a> std::iter::Sum<&'a $vector> for $vector {
+            fn sum<I: Iterator<Item = &'a $vector>>(iter: I) -> $vector {
+                iter.copied().sum()
+            }
+        }
+    };
+}
+
+pub(crate) use impl_iterators;
@@ -1,11 +1,18 @@
-use std::ops::{self, Add, AddAssign, Div, Index, Mul, MulAssign, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
+};
 
-use approx::{AbsDiffEq, RelativeEq};
-use num::{Num, ToPrimitive, pow};
+use approx::AbsDiffEq;
+use num::{ToPrimitive, pow};
 
-use crate::vector::vector::Vector;
+use crate::{
+    matrix::rotation::Angle,
+    vector::vector::{Vector, impl_iterators, write_components},
+};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
@@ -22,12 +29,37 @@ impl Vector2 {
             y: y.to_f32().expect("Not a number"),
         }
     }
+
+    pub const fn from_array([x, y]: [f32; 2]) -> Vector2 {
+        Vector2 { x, y }
+    }
+
+    pub const fn to_array(&self) -> [f32; 2] {
+        [self.x, self.y]
+    }
+
+    // Components in order, starting from x
+    pub fn components(&self) -> array::IntoIter<f32, 2> {
+        self.to_array().into_iter()
+    }
+
+    // Rotated 90 degrees counter-clockwise
+    pub fn perp(&self) -> Vector2 {
+        Vector2::new(-self.y, self.x)
+    }
+
+    // Rotates counter-clockwise about the origin
+    pub fn rotate(&self, angle: Angle) -> Vector2 {
+        let angle = angle.to_radians();
+
+        let cos = f32::cos(angle);
+        let sin = f32::sin(angle);
+        Vector2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
 }
 
 // Vector utilities
 impl Vector for Vector2 {
-    type VectorType = Vector2;
-
     fn length(&self) -> f32 {
         f32::sqrt(pow(self.x, 2) + pow(self.y, 2))
     }
@@ -40,6 +72,41 @@ impl Vector for Vector2 {
     fn dot(&self, other: Vector2) -> f32 {
         self.x * other.x + self.y * other.y
     }
+
+    fn clamp(&self, min: Vector2, max: Vector2) -> Vector2 {
+        Vector2::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+
+    fn clamp_scalar(&self, min: f32, max: f32) -> Vector2 {
+        Vector2::new(self.x.clamp(min, max), self.y.clamp(min, max))
+    }
+
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Vector2::new(f(self.x), f(self.y))
+    }
+
+    fn zip_map(&self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Vector2::new(f(self.x, other.x), f(self.y, other.y))
+    }
+}
+
+impl From<(f32, f32)> for Vector2 {
+    fn from((x, y): (f32, f32)) -> Self {
+        Vector2 { x, y }
+    }
+}
+
+impl From<Vector2> for (f32, f32) {
+    fn from(vec: Vector2) -> Self {
+        (vec.x, vec.y)
+    }
+}
+
+// Formats as (x, y), e.g. format!("{v:.2}") limits each component to two decimals
+impl fmt::Display for Vector2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_components(f, &self.to_array())
+    }
 }
 
 // Vector addition
@@ -119,6 +186,43 @@ impl MulAssign<f32> for Vector2 {
     }
 }
 
+impl MulAssign<Vector2> for Vector2 {
+    fn mul_assign(&mut self, other: Vector2) {
+        *self = *self * other;
+    }
+}
+
+// Compound addition and subtraction
+impl AddAssign<Vector2> for Vector2 {
+    fn add_assign(&mut self, other: Vector2) {
+        *self = *self + other;
+    }
+}
+
+impl AddAssign<f32> for Vector2 {
+    fn add_assign(&mut self, scalar: f32) {
+        *self = *self + scalar;
+    }
+}
+
+impl SubAssign<Vector2> for Vector2 {
+    fn sub_assign(&mut self, other: Vector2) {
+        *self = *self - other;
+    }
+}
+
+impl SubAssign<f32> for Vector2 {
+    fn sub_assign(&mut self, scalar: f32) {
+        *self = *self - scalar;
+    }
+}
+
+impl DivAssign<f32> for Vector2 {
+    fn div_assign(&mut self, scalar: f32) {
+        *self = *self / scalar;
+    }
+}
+
 // Scalar-Vector division
 impl Div<f32> for Vector2 {
     type Output = Vector2;
@@ -142,6 +246,8 @@ impl Neg for Vector2 {
     }
 }
 
+impl_iterators!(Vector2, 2);
+
 // Allows us to index instead of using members
 impl Index<usize> for Vector2 {
     type Output = f32;
@@ -155,6 +261,16 @@ impl Index<usize> for Vector2 {
     }
 }
 
+impl IndexMut<usize> for Vector2 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Vector2
 where
@@ -279,4 +395,223 @@ mod tests {
         assert_eq!(a[0], 10.0);
         assert_eq!(a[1], 5.0);
     }
+
+    #[test]
+    fn compound_assignment_test() {
+        let a = Vector2::new(10, 4);
+        let b = Vector2::new(2, 3);
+
+        let mut v = a;
+        v += b;
+        assert_eq!(v, a + b);
+
+        let mut v = a;
+        v -= b;
+        assert_eq!(v, a - b);
+
+        let mut v = a;
+        v *= b;
+        assert_eq!(v, a * b);
+
+        let mut v = a;
+        v += 2.0;
+        assert_eq!(v, a + 2.0);
+
+        let mut v = a;
+        v -= 2.0;
+        assert_eq!(v, a - 2.0);
+
+        let mut v = a;
+        v *= 2.0;
+        assert_eq!(v, a * 2.0);
+
+        let mut v = a;
+        v /= 2.0;
+        assert_eq!(v, a / 2.0);
+    }
+
+    #[test]
+    fn reflect_test() {
+        let v = Vector2::new(1, -1);
+        let normal = Vector2::new(0, 1);
+        assert_eq!(v.reflect(normal), Vector2::new(1, 1));
+    }
+
+    #[test]
+    fn lerp_test() {
+        let a = Vector2::new(0, 2);
+        let b = Vector2::new(4, -2);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Vector2::new(2, 0));
+    }
+
+    #[test]
+    fn clamp_test() {
+        let v = Vector2::new(-3, 5);
+        assert_eq!(
+            v.clamp(Vector2::new(-1, 0), Vector2::new(1, 2)),
+            Vector2::new(-1, 2)
+        );
+        assert_eq!(v.clamp_scalar(0.0, 1.0), Vector2::new(0, 1));
+    }
+
+    #[test]
+    fn project_and_reject_test() {
+        let v = Vector2::new(3, 4);
+        let axis = Vector2::new(2, 0);
+        assert_eq!(v.project_onto(axis), Vector2::new(3, 0));
+        assert_eq!(v.reject_from(axis), Vector2::new(0, 4));
+
+        // Projecting onto a zero vector doesn't produce NaNs
+        assert_eq!(v.project_onto(Vector2::new(0, 0)), Vector2::new(0, 0));
+    }
+
+    #[test]
+    fn angle_between_test() {
+        let x = Vector2::new(1, 0);
+        assert_abs_diff_eq!(
+            x.angle_between(Vector2::new(0, 3)),
+            std::f32::consts::FRAC_PI_2
+        );
+        assert_abs_diff_eq!(x.angle_between(Vector2::new(5, 0)), 0.0);
+        assert_abs_diff_eq!(x.angle_between(Vector2::new(-2, 0)), std::f32::consts::PI);
+        assert_eq!(x.angle_between(Vector2::new(0, 0)), 0.0);
+    }
+
+    #[test]
+    fn distance_test() {
+        let a = Vector2::new(1, 1);
+        let b = Vector2::new(4, 5);
+        assert_eq!(a.distance(b), 5.0);
+        assert_eq!(b.distance(a), 5.0);
+        assert_eq!(a.distance_squared(b), 25.0);
+        assert_eq!(a.distance(a), 0.0);
+    }
+
+    #[test]
+    fn array_conversion_test() {
+        let v = Vector2::new(1.5, -2.0);
+        assert_eq!(v.to_array(), [1.5, -2.0]);
+        assert_eq!(Vector2::from_array(v.to_array()), v);
+    }
+
+    #[test]
+    fn tuple_conversion_test() {
+        let v = Vector2::from((1.5, -2.0));
+        assert_eq!(v, Vector2::new(1.5, -2.0));
+
+        let tuple: (f32, f32) = v.into();
+        assert_eq!(tuple, (1.5, -2.0));
+    }
+
+    #[test]
+    fn default_test() {
+        assert_eq!(Vector2::default(), Vector2::new(0, 0));
+    }
+
+    #[test]
+    fn display_test() {
+        let v = Vector2::new(3, -0.5);
+        assert_eq!(v.to_string(), "(3, -0.5)");
+        assert_eq!(format!("{v:.1}"), "(3.0, -0.5)");
+    }
+
+    #[test]
+    fn index_mut_test() {
+        let mut v = Vector2::new(0, 0);
+        for i in 0..2 {
+            v[i] = i as f32 + 1.0;
+        }
+        assert_eq!(v, Vector2::new(1, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut v = Vector2::new(0, 0);
+        v[2] = 1.0;
+    }
+
+    #[test]
+    fn approx_eq_test() {
+        let a = Vector2::new(1, 1).normalize();
+        let b = Vector2::new(f32::sqrt(0.5), f32::sqrt(0.5));
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(!a.approx_eq(&Vector2::new(0.7, 0.7), 1e-6));
+    }
+
+    #[test]
+    fn perp_test() {
+        let v = Vector2::new(3, -2);
+        assert_eq!(v.perp(), Vector2::new(2, 3));
+        assert_eq!(v.dot(v.perp()), 0.0);
+        assert_eq!(v.perp().perp(), -v);
+    }
+
+    #[test]
+    fn rotate_test() {
+        let v = Vector2::new(1, 0);
+        assert_abs_diff_eq!(
+            v.rotate(Angle::Degrees(90.0)),
+            Vector2::new(0, 1),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            v.rotate(Angle::Radians(std::f32::consts::PI)),
+            Vector2::new(-1, 0),
+            epsilon = 1e-6
+        );
+
+        // A quarter turn is the same as perp, and the length never changes
+        let v = Vector2::new(2.5, -4);
+        assert_abs_diff_eq!(v.rotate(Angle::Degrees(90.0)), v.perp(), epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            v.rotate(Angle::Degrees(-33.0)).length(),
+            v.length(),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn min_max_test() {
+        let a = Vector2::new(-1.5, 4);
+        let b = Vector2::new(2, -3);
+        assert_eq!(a.min(b), Vector2::new(-1.5, -3));
+        assert_eq!(a.max(b), Vector2::new(2, 4));
+    }
+
+    #[test]
+    fn rounding_test() {
+        let v = Vector2::new(-1.5, 2.4);
+        assert_eq!(v.abs(), Vector2::new(1.5, 2.4));
+        assert_eq!(v.floor(), Vector2::new(-2, 2));
+        assert_eq!(v.ceil(), Vector2::new(-1, 3));
+        assert_eq!(v.round(), Vector2::new(-2, 2));
+    }
+
+    #[test]
+    fn try_normalize_test() {
+        assert_abs_diff_eq!(
+            Vector2::new(3, 4).try_normalize().unwrap(),
+            Vector2::new(0.6, 0.8)
+        );
+        assert_eq!(Vector2::new(0, 0).try_normalize(), None);
+        assert_eq!(Vector2::new(1e-8, -1e-8).try_normalize(), None);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let v = Vector2::new(1.5, -2);
+        assert_eq!(v.components().collect::<Vec<_>>(), vec![1.5, -2.0]);
+        assert_eq!(v.into_iter().map(f32::abs).sum::<f32>(), 3.5);
+
+        let points = [Vector2::new(0, 0), Vector2::new(4, 0), Vector2::new(2, 6)];
+        assert_eq!(points.iter().sum::<Vector2>(), Vector2::new(6, 6));
+        assert_eq!(
+            points.into_iter().sum::<Vector2>() / 3.0,
+            Vector2::new(2, 2)
+        );
+        assert_eq!(points[..0].iter().sum::<Vector2>(), Vector2::default());
+    }
 }
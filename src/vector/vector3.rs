@@ -1,11 +1,18 @@
-use std::ops::{self, Add, Div, Index, Mul, MulAssign, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
+};
 
-use approx::{AbsDiffEq, RelativeEq};
+use approx::AbsDiffEq;
 use num::{ToPrimitive, pow};
 
-use crate::{Vector2, Vector4, vector::vector::Vector};
+use crate::{
+    Vector2, Vector4,
+    vector::vector::{Vector, impl_iterators, write_components},
+};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -26,6 +33,19 @@ impl Vector3 {
         }
     }
 
+    pub const fn from_array([x, y, z]: [f32; 3]) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+
+    pub const fn to_array(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    // Components in order, starting from x
+    pub fn components(&self) -> array::IntoIter<f32, 3> {
+        self.to_array().into_iter()
+    }
+
     pub fn cross(&self, other: Vector3) -> Vector3 {
         Vector3::new(
             self.y * other.z - self.z * other.y,
@@ -38,7 +58,12 @@ impl Vector3 {
         Vector3::new(vec.x, vec.y, vec.z)
     }
 
+    // Perspective divide, directions (w = 0) have no point to divide into so they're kept as is
     pub fn to_cartesian(vec: Vector4) -> Vector3 {
+        if vec.w == 0.0 {
+            return Vector3::to_vector3(vec);
+        }
+
         Vector3::new(vec.x / vec.w, vec.y / vec.w, vec.z / vec.w)
     }
 
@@ -48,20 +73,68 @@ impl Vector3 {
 }
 
 impl Vector for Vector3 {
-    type VectorType = Vector3;
-
     fn length(&self) -> f32 {
         f32::sqrt(pow(self.x, 2) + pow(self.y, 2) + pow(self.z, 2))
     }
 
-    fn normalize(&self) -> Self::VectorType {
+    fn normalize(&self) -> Self {
         let length = self.length();
         *self / length
     }
 
-    fn dot(&self, other: Self::VectorType) -> f32 {
+    fn dot(&self, other: Self) -> f32 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
+
+    fn clamp(&self, min: Self, max: Self) -> Self {
+        Vector3::new(
+            self.x.clamp(min.x, max.x),
+            self.y.clamp(min.y, max.y),
+            self.z.clamp(min.z, max.z),
+        )
+    }
+
+    fn clamp_scalar(&self, min: f32, max: f32) -> Self {
+        Vector3::new(
+            self.x.clamp(min, max),
+            self.y.clamp(min, max),
+            self.z.clamp(min, max),
+        )
+    }
+
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Vector3::new(f(self.x), f(self.y), f(self.z))
+    }
+
+    fn zip_map(&self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Vector3::new(f(self.x, other.x), f(self.y, other.y), f(self.z, other.z))
+    }
+}
+
+// Embeds a 2D vector in the z = 0 plane
+impl From<Vector2> for Vector3 {
+    fn from(vec: Vector2) -> Self {
+        Vector3::new(vec.x, vec.y, 0.0)
+    }
+}
+
+impl From<(f32, f32, f32)> for Vector3 {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Vector3 { x, y, z }
+    }
+}
+
+impl From<Vector3> for (f32, f32, f32) {
+    fn from(vec: Vector3) -> Self {
+        (vec.x, vec.y, vec.z)
+    }
+}
+
+// Formats as (x, y, z), e.g. format!("{v:.2}") limits each component to two decimals
+impl fmt::Display for Vector3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_components(f, &self.to_array())
+    }
 }
 
 // Vector addition
@@ -142,6 +215,43 @@ impl MulAssign<f32> for Vector3 {
     }
 }
 
+impl MulAssign<Vector3> for Vector3 {
+    fn mul_assign(&mut self, other: Vector3) {
+        *self = *self * other;
+    }
+}
+
+// Compound addition and subtraction
+impl AddAssign<Vector3> for Vector3 {
+    fn add_assign(&mut self, other: Vector3) {
+        *self = *self + other;
+    }
+}
+
+impl AddAssign<f32> for Vector3 {
+    fn add_assign(&mut self, scalar: f32) {
+        *self = *self + scalar;
+    }
+}
+
+impl SubAssign<Vector3> for Vector3 {
+    fn sub_assign(&mut self, other: Vector3) {
+        *self = *self - other;
+    }
+}
+
+impl SubAssign<f32> for Vector3 {
+    fn sub_assign(&mut self, scalar: f32) {
+        *self = *self - scalar;
+    }
+}
+
+impl DivAssign<f32> for Vector3 {
+    fn div_assign(&mut self, scalar: f32) {
+        *self = *self / scalar;
+    }
+}
+
 // Scalar-Vector division
 impl Div<f32> for Vector3 {
     type Output = Vector3;
@@ -165,6 +275,8 @@ impl Neg for Vector3 {
     }
 }
 
+impl_iterators!(Vector3, 3);
+
 // Allows us to index instead of using members
 impl Index<usize> for Vector3 {
     type Output = f32;
@@ -179,6 +291,17 @@ impl Index<usize> for Vector3 {
     }
 }
 
+impl IndexMut<usize> for Vector3 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Vector3
 where
@@ -245,6 +368,14 @@ mod tests {
         assert_eq!(vec + 2., res);
     }
 
+    #[test]
+    fn scalar_addition_components_test() {
+        let res = 2.0 + Vector3::new(1, 2, 3);
+        assert_eq!(res.x, 3.0);
+        assert_eq!(res.y, 4.0);
+        assert_eq!(res.z, 5.0);
+    }
+
     #[test]
     fn scalar_subtraction_test() {
         let vec: Vector3 = Vector3::new(10., 20., 5.);
@@ -289,6 +420,27 @@ mod tests {
         assert_eq!(a.cross(b), res);
     }
 
+    #[test]
+    fn cross_product_orientation_test() {
+        let x = Vector3::new(1, 0, 0);
+        let y = Vector3::new(0, 1, 0);
+        let z = Vector3::new(0, 0, 1);
+
+        // Right-hand rule
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(z), x);
+        assert_eq!(z.cross(x), y);
+    }
+
+    #[test]
+    fn cross_product_anti_commutative_test() {
+        let a = Vector3::new(1, 2, 3);
+        let b = Vector3::new(12, 4, 5);
+
+        assert_eq!(b.cross(a), -a.cross(b));
+        assert_eq!(a.cross(a), Vector3::new(0, 0, 0));
+    }
+
     #[test]
     fn hadamard_product_test() {
         let a: Vector3 = Vector3::new(10, 2., 12.);
@@ -322,4 +474,247 @@ mod tests {
 
         assert_abs_diff_eq!(a.normalize(), b);
     }
+
+    #[test]
+    fn compound_assignment_test() {
+        let a = Vector3::new(10, 4, 6);
+        let b = Vector3::new(2, 3, 5);
+
+        let mut v = a;
+        v += b;
+        assert_eq!(v, a + b);
+
+        let mut v = a;
+        v -= b;
+        assert_eq!(v, a - b);
+
+        let mut v = a;
+        v *= b;
+        assert_eq!(v, a * b);
+
+        let mut v = a;
+        v += 2.0;
+        assert_eq!(v, a + 2.0);
+
+        let mut v = a;
+        v -= 2.0;
+        assert_eq!(v, a - 2.0);
+
+        let mut v = a;
+        v *= 2.0;
+        assert_eq!(v, a * 2.0);
+
+        let mut v = a;
+        v /= 2.0;
+        assert_eq!(v, a / 2.0);
+    }
+
+    #[test]
+    fn reflect_test() {
+        let v = Vector3::new(1, -2, 3);
+        let normal = Vector3::new(0, 1, 0);
+        assert_eq!(v.reflect(normal), Vector3::new(1, 2, 3));
+
+        // Reflecting twice gives the original vector
+        assert_eq!(v.reflect(normal).reflect(normal), v);
+    }
+
+    #[test]
+    fn refract_test() {
+        let normal = Vector3::new(0, 1, 0);
+
+        // Head on rays pass straight through
+        let v = Vector3::new(0, -1, 0);
+        assert_abs_diff_eq!(v.refract(normal, 1.0 / 1.5).unwrap(), v);
+
+        // Matching refractive indices don't bend the ray
+        let v = Vector3::new(1, -1, 0).normalize();
+        assert_abs_diff_eq!(v.refract(normal, 1.0).unwrap(), v, epsilon = 1e-6);
+
+        // Leaving a denser medium at a grazing angle is totally internally reflected
+        let v = Vector3::new(1.0, -0.1, 0.0).normalize();
+        assert_eq!(v.refract(normal, 1.5), None);
+    }
+
+    #[test]
+    fn lerp_test() {
+        let a = Vector3::new(0, 2, 1);
+        let b = Vector3::new(4, -2, 1);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Vector3::new(2, 0, 1));
+        assert_eq!(a.lerp(b, 0.25), Vector3::new(1, 1, 1));
+    }
+
+    #[test]
+    fn clamp_test() {
+        let v = Vector3::new(-3, 5, 0.5);
+        let min = Vector3::new(-1, 0, 0);
+        let max = Vector3::new(1, 2, 1);
+        assert_eq!(v.clamp(min, max), Vector3::new(-1, 2, 0.5));
+        assert_eq!(v.clamp_scalar(0.0, 1.0), Vector3::new(0, 1, 0.5));
+    }
+
+    #[test]
+    fn project_and_reject_test() {
+        let v = Vector3::new(1, 2, 3);
+        let axis = Vector3::new(0, 0, 5);
+        assert_eq!(v.project_onto(axis), Vector3::new(0, 0, 3));
+        assert_eq!(v.reject_from(axis), Vector3::new(1, 2, 0));
+
+        // The projection and rejection add back up to the original vector
+        let other = Vector3::new(2, -1, 1);
+        assert_abs_diff_eq!(
+            v.project_onto(other) + v.reject_from(other),
+            v,
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(v.reject_from(other).dot(other), 0.0, epsilon = 1e-6);
+
+        // Projecting onto a zero vector doesn't produce NaNs
+        assert_eq!(v.project_onto(Vector3::new(0, 0, 0)), Vector3::new(0, 0, 0));
+    }
+
+    #[test]
+    fn angle_between_test() {
+        let x = Vector3::new(1, 0, 0);
+        assert_abs_diff_eq!(
+            x.angle_between(Vector3::new(0, 2, 0)),
+            std::f32::consts::FRAC_PI_2
+        );
+        assert_abs_diff_eq!(x.angle_between(Vector3::new(3, 0, 0)), 0.0);
+        assert_abs_diff_eq!(
+            x.angle_between(Vector3::new(1, 1, 0)),
+            std::f32::consts::FRAC_PI_4
+        );
+        assert_eq!(x.angle_between(Vector3::new(0, 0, 0)), 0.0);
+    }
+
+    #[test]
+    fn distance_test() {
+        let a = Vector3::new(1, 2, 3);
+        let b = Vector3::new(3, 5, 9);
+        assert_eq!(a.distance(b), 7.0);
+        assert_eq!(a.distance_squared(b), 49.0);
+
+        // Squared distance is exact where the square root isn't representable
+        let c = Vector3::new(2, 3, 4);
+        assert_eq!(a.distance_squared(c), 3.0);
+        assert_abs_diff_eq!(a.distance(c), f32::sqrt(3.0));
+    }
+
+    #[test]
+    fn from_vector2_test() {
+        let v: Vector3 = Vector2::new(1, 2).into();
+        assert_eq!(v, Vector3::new(1, 2, 0));
+    }
+
+    #[test]
+    fn array_conversion_test() {
+        let v = Vector3::new(1.5, -2.0, 3.25);
+        assert_eq!(v.to_array(), [1.5, -2.0, 3.25]);
+        assert_eq!(Vector3::from_array(v.to_array()), v);
+    }
+
+    #[test]
+    fn tuple_conversion_test() {
+        let v = Vector3::from((1.5, -2.0, 3.25));
+        assert_eq!(v, Vector3::new(1.5, -2.0, 3.25));
+
+        let tuple: (f32, f32, f32) = v.into();
+        assert_eq!(tuple, (1.5, -2.0, 3.25));
+    }
+
+    #[test]
+    fn default_test() {
+        assert_eq!(Vector3::default(), Vector3::new(0, 0, 0));
+    }
+
+    #[test]
+    fn display_test() {
+        let v = Vector3::new(1, -2.5, 0.125);
+        assert_eq!(v.to_string(), "(1, -2.5, 0.125)");
+        assert_eq!(format!("{v:.2}"), "(1.00, -2.50, 0.12)");
+    }
+
+    #[test]
+    fn index_mut_test() {
+        let mut v = Vector3::new(0, 0, 0);
+        for i in 0..3 {
+            v[i] = i as f32 + 1.0;
+        }
+        assert_eq!(v, Vector3::new(1, 2, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut v = Vector3::new(0, 0, 0);
+        v[3] = 1.0;
+    }
+
+    #[test]
+    fn approx_eq_test() {
+        let a = Vector3::new(0.1, 0.2, 0.3);
+        let b = Vector3::new(0.1 + 1e-7, 0.2, 0.3 - 1e-7);
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(!a.approx_eq(&Vector3::new(0.1, 0.2, 0.31), 1e-6));
+    }
+
+    #[test]
+    fn min_max_test() {
+        let a = Vector3::new(-1.5, 4, 0);
+        let b = Vector3::new(2, -3, -0.5);
+        assert_eq!(a.min(b), Vector3::new(-1.5, -3, -0.5));
+        assert_eq!(a.max(b), Vector3::new(2, 4, 0));
+    }
+
+    #[test]
+    fn rounding_test() {
+        let v = Vector3::new(-1.5, 2.4, -0.6);
+        assert_eq!(v.abs(), Vector3::new(1.5, 2.4, 0.6));
+        assert_eq!(v.floor(), Vector3::new(-2, 2, -1));
+        assert_eq!(v.ceil(), Vector3::new(-1, 3, 0));
+        assert_eq!(v.round(), Vector3::new(-2, 2, -1));
+    }
+
+    #[test]
+    fn try_normalize_test() {
+        assert_abs_diff_eq!(
+            Vector3::new(0, 3, 4).try_normalize().unwrap(),
+            Vector3::new(0, 0.6, 0.8)
+        );
+        assert_eq!(Vector3::new(0, 0, 0).try_normalize(), None);
+        assert_eq!(Vector3::new(1e-8, 0, -1e-8).try_normalize(), None);
+
+        // Plain normalize has nothing to divide by
+        assert!(Vector3::new(0, 0, 0).normalize().x.is_nan());
+    }
+
+    #[test]
+    fn iterator_test() {
+        let v = Vector3::new(1.5, -2, 3.25);
+        assert_eq!(v.components().collect::<Vec<_>>(), vec![1.5, -2.0, 3.25]);
+        assert_eq!(v.into_iter().fold(f32::MIN, f32::max), 3.25);
+        assert_eq!(v.components().next_back(), Some(3.25));
+    }
+
+    #[test]
+    fn sum_test() {
+        let vertices = [
+            Vector3::new(1, 0, -1),
+            Vector3::new(-1, 2, -1),
+            Vector3::new(3, 4, -1),
+            Vector3::new(1, -2, -5),
+        ];
+        assert_eq!(vertices.iter().sum::<Vector3>(), Vector3::new(4, 4, -8));
+
+        // Centroid of the vertices
+        let centroid = vertices.iter().copied().sum::<Vector3>() / vertices.len() as f32;
+        assert_abs_diff_eq!(centroid, Vector3::new(1, 1, -2));
+
+        // Nothing adds up to zero
+        assert_eq!(vertices[..0].iter().sum::<Vector3>(), Vector3::default());
+    }
 }
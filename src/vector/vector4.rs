@@ -1,14 +1,19 @@
-use std::ops::{self, Add, Div, Index, Mul, MulAssign, Neg, Sub};
+use std::{
+    array, fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
+};
 
 use approx::AbsDiffEq;
 use num::{ToPrimitive, pow};
 
-use crate::{
-    Vector2,
-    vector::{vector::Vector, vector3::Vector3},
+use crate::vector::{
+    simd,
+    vector::{Vector, impl_iterators, write_components},
+    vector3::Vector3,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector4 {
     pub x: f32,
     pub y: f32,
@@ -32,6 +37,19 @@ impl Vector4 {
         }
     }
 
+    pub const fn from_array([x, y, z, w]: [f32; 4]) -> Vector4 {
+        Vector4 { x, y, z, w }
+    }
+
+    pub const fn to_array(&self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
+    // Components in order, starting from x
+    pub fn components(&self) -> array::IntoIter<f32, 4> {
+        self.to_array().into_iter()
+    }
+
     pub fn to_homogeneous(v: Vector3) -> Vector4 {
         Vector4::to_vector4(v, 1.0)
     }
@@ -51,19 +69,74 @@ impl Vector4 {
 }
 
 impl Vector for Vector4 {
-    type VectorType = Vector4;
-
     fn length(&self) -> f32 {
         f32::sqrt(pow(self.x, 2) + pow(self.y, 2) + pow(self.z, 2) + pow(self.w, 2))
     }
 
-    fn normalize(&self) -> Self::VectorType {
+    fn normalize(&self) -> Self {
         let length = self.length();
         *self * (1. / length)
     }
 
-    fn dot(&self, other: Self::VectorType) -> f32 {
-        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    fn dot(&self, other: Self) -> f32 {
+        simd::dot(self.to_array(), other.to_array())
+    }
+
+    fn clamp(&self, min: Self, max: Self) -> Self {
+        Vector4::new(
+            self.x.clamp(min.x, max.x),
+            self.y.clamp(min.y, max.y),
+            self.z.clamp(min.z, max.z),
+            self.w.clamp(min.w, max.w),
+        )
+    }
+
+    fn clamp_scalar(&self, min: f32, max: f32) -> Self {
+        Vector4::new(
+            self.x.clamp(min, max),
+            self.y.clamp(min, max),
+            self.z.clamp(min, max),
+            self.w.clamp(min, max),
+        )
+    }
+
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Vector4::new(f(self.x), f(self.y), f(self.z), f(self.w))
+    }
+
+    fn zip_map(&self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Vector4::new(
+            f(self.x, other.x),
+            f(self.y, other.y),
+            f(self.z, other.z),
+            f(self.w, other.w),
+        )
+    }
+}
+
+// Treats the vector as a point in homogeneous coordinates
+impl From<Vector3> for Vector4 {
+    fn from(vec: Vector3) -> Self {
+        Vector4::to_homogeneous(vec)
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for Vector4 {
+    fn from((x, y, z, w): (f32, f32, f32, f32)) -> Self {
+        Vector4 { x, y, z, w }
+    }
+}
+
+impl From<Vector4> for (f32, f32, f32, f32) {
+    fn from(vec: Vector4) -> Self {
+        (vec.x, vec.y, vec.z, vec.w)
+    }
+}
+
+// Formats as (x, y, z, w), e.g. format!("{v:.2}") limits each component to two decimals
+impl fmt::Display for Vector4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_components(f, &self.to_array())
     }
 }
 
@@ -72,12 +145,7 @@ impl Add<Vector4> for Vector4 {
     type Output = Vector4;
 
     fn add(self, other: Vector4) -> Vector4 {
-        Vector4::new(
-            other.x + self.x,
-            other.y + self.y,
-            other.z + self.z,
-            other.w + self.w,
-        )
+        Vector4::from_array(simd::add(self.to_array(), other.to_array()))
     }
 }
 
@@ -86,12 +154,7 @@ impl Sub<Vector4> for Vector4 {
     type Output = Vector4;
 
     fn sub(self, other: Vector4) -> Vector4 {
-        Vector4::new(
-            self.x - other.x,
-            self.y - other.y,
-            self.z - other.z,
-            self.w - other.w,
-        )
+        Vector4::from_array(simd::sub(self.to_array(), other.to_array()))
     }
 }
 
@@ -140,12 +203,7 @@ impl Sub<Vector4> for f32 {
 impl Mul<Vector4> for Vector4 {
     type Output = Vector4;
     fn mul(self, other: Vector4) -> Vector4 {
-        Vector4::new(
-            self.x * other.x,
-            self.y * other.y,
-            self.z * other.z,
-            self.w * other.w,
-        )
+        Vector4::from_array(simd::mul(self.to_array(), other.to_array()))
     }
 }
 
@@ -153,19 +211,14 @@ impl Mul<Vector4> for Vector4 {
 impl Mul<f32> for Vector4 {
     type Output = Vector4;
     fn mul(self, scalar: f32) -> Vector4 {
-        Vector4::new(
-            scalar * self.x,
-            scalar * self.y,
-            scalar * self.z,
-            scalar * self.w,
-        )
+        Vector4::from_array(simd::scale(self.to_array(), scalar))
     }
 }
 
 impl Mul<Vector4> for f32 {
     type Output = Vector4;
     fn mul(self, vec: Vector4) -> Vector4 {
-        Vector4::new(self * vec.x, self * vec.y, self * vec.z, self * vec.w)
+        vec * self
     }
 }
 
@@ -175,6 +228,43 @@ impl MulAssign<f32> for Vector4 {
     }
 }
 
+impl MulAssign<Vector4> for Vector4 {
+    fn mul_assign(&mut self, other: Vector4) {
+        *self = *self * other;
+    }
+}
+
+// Compound addition and subtraction
+impl AddAssign<Vector4> for Vector4 {
+    fn add_assign(&mut self, other: Vector4) {
+        *self = *self + other;
+    }
+}
+
+impl AddAssign<f32> for Vector4 {
+    fn add_assign(&mut self, scalar: f32) {
+        *self = *self + scalar;
+    }
+}
+
+impl SubAssign<Vector4> for Vector4 {
+    fn sub_assign(&mut self, other: Vector4) {
+        *self = *self - other;
+    }
+}
+
+impl SubAssign<f32> for Vector4 {
+    fn sub_assign(&mut self, scalar: f32) {
+        *self = *self - scalar;
+    }
+}
+
+impl DivAssign<f32> for Vector4 {
+    fn div_assign(&mut self, scalar: f32) {
+        *self = *self / scalar;
+    }
+}
+
 // Scalar-vector division
 impl Div<f32> for Vector4 {
     type Output = Vector4;
@@ -203,6 +293,8 @@ impl Neg for Vector4 {
     }
 }
 
+impl_iterators!(Vector4, 4);
+
 // Allows us to index instead of using members
 impl Index<usize> for Vector4 {
     type Output = f32;
@@ -218,6 +310,18 @@ impl Index<usize> for Vector4 {
     }
 }
 
+impl IndexMut<usize> for Vector4 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            3 => &mut self.w,
+            _ => panic!("Index out of range"),
+        }
+    }
+}
+
 // For approximate equals
 impl AbsDiffEq for Vector4
 where
@@ -241,6 +345,7 @@ where
     }
 }
 
+#[cfg(test)]
 mod tests {
     use approx::assert_abs_diff_eq;
 
@@ -286,6 +391,15 @@ mod tests {
         assert_abs_diff_eq!(vec + 2., res);
     }
 
+    #[test]
+    fn scalar_addition_components_test() {
+        let res = 2.0 + Vector4::new(1, 2, 3, 4);
+        assert_eq!(res.x, 3.0);
+        assert_eq!(res.y, 4.0);
+        assert_eq!(res.z, 5.0);
+        assert_eq!(res.w, 6.0);
+    }
+
     #[test]
     fn scalar_subtraction_test() {
         let vec: Vector4 = Vector4::new(10., 20., 5., 44.);
@@ -312,6 +426,14 @@ mod tests {
         assert_abs_diff_eq!(b - a, -res);
     }
 
+    #[test]
+    fn vector_subtraction_order_test() {
+        let a = Vector4::new(5, 5, 5, 5);
+        let b = Vector4::new(1, 2, 3, 4);
+        assert_eq!(a - b, Vector4::new(4, 3, 2, 1));
+        assert_eq!(b - a, -(a - b));
+    }
+
     #[test]
     fn dot_product_test() {
         let a = Vector4::new(1, 2, 3, 6);
@@ -355,4 +477,208 @@ mod tests {
 
         assert_abs_diff_eq!(a.normalize(), b);
     }
+
+    #[test]
+    fn compound_assignment_test() {
+        let a = Vector4::new(10, 4, 6, 8);
+        let b = Vector4::new(2, 3, 5, 7);
+
+        let mut v = a;
+        v += b;
+        assert_eq!(v, a + b);
+
+        let mut v = a;
+        v -= b;
+        assert_eq!(v, a - b);
+
+        let mut v = a;
+        v *= b;
+        assert_eq!(v, a * b);
+
+        let mut v = a;
+        v += 2.0;
+        assert_eq!(v, a + 2.0);
+
+        let mut v = a;
+        v -= 2.0;
+        assert_eq!(v, a - 2.0);
+
+        let mut v = a;
+        v *= 2.0;
+        assert_eq!(v, a * 2.0);
+
+        let mut v = a;
+        v /= 2.0;
+        assert_eq!(v, a / 2.0);
+    }
+
+    #[test]
+    fn reflect_test() {
+        let v = Vector4::new(1, -2, 3, 4);
+        let normal = Vector4::new(0, 1, 0, 0);
+        assert_eq!(v.reflect(normal), Vector4::new(1, 2, 3, 4));
+    }
+
+    #[test]
+    fn lerp_test() {
+        let a = Vector4::new(0, 2, 1, 1);
+        let b = Vector4::new(4, -2, 1, 3);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Vector4::new(2, 0, 1, 2));
+    }
+
+    #[test]
+    fn clamp_test() {
+        let v = Vector4::new(-3, 5, 0.5, 2);
+        let min = Vector4::new(-1, 0, 0, 0);
+        let max = Vector4::new(1, 2, 1, 1);
+        assert_eq!(v.clamp(min, max), Vector4::new(-1, 2, 0.5, 1));
+        assert_eq!(v.clamp_scalar(0.0, 1.0), Vector4::new(0, 1, 0.5, 1));
+    }
+
+    #[test]
+    fn project_and_reject_test() {
+        let v = Vector4::new(1, 2, 3, 4);
+        let axis = Vector4::new(0, 0, 0, 2);
+        assert_eq!(v.project_onto(axis), Vector4::new(0, 0, 0, 4));
+        assert_eq!(v.reject_from(axis), Vector4::new(1, 2, 3, 0));
+
+        // Projecting onto a zero vector doesn't produce NaNs
+        assert_eq!(
+            v.project_onto(Vector4::new(0, 0, 0, 0)),
+            Vector4::new(0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn angle_between_test() {
+        let x = Vector4::new(1, 0, 0, 0);
+        assert_abs_diff_eq!(
+            x.angle_between(Vector4::new(0, 0, 0, 1)),
+            std::f32::consts::FRAC_PI_2
+        );
+        assert_abs_diff_eq!(x.angle_between(Vector4::new(2, 0, 0, 0)), 0.0);
+        assert_eq!(x.angle_between(Vector4::new(0, 0, 0, 0)), 0.0);
+    }
+
+    #[test]
+    fn distance_test() {
+        let a = Vector4::new(1, 1, 1, 1);
+        let b = Vector4::new(2, 2, 2, 2);
+        assert_eq!(a.distance(b), 2.0);
+        assert_eq!(a.distance_squared(b), 4.0);
+    }
+
+    #[test]
+    fn homogeneous_round_trip_test() {
+        let point = Vector3::new(1, -2, 3);
+        let homogeneous: Vector4 = point.into();
+        assert_eq!(homogeneous, Vector4::new(1, -2, 3, 1));
+        assert_eq!(homogeneous.cartesian(), point);
+    }
+
+    #[test]
+    fn perspective_divide_test() {
+        let v = Vector4::new(2, -4, 1, 2);
+        assert_eq!(v.cartesian(), Vector3::new(1, -2, 0.5));
+
+        // Directions have no w to divide by
+        let direction = Vector4::new(1, 2, 3, 0);
+        assert_eq!(direction.cartesian(), Vector3::new(1, 2, 3));
+    }
+
+    #[test]
+    fn array_conversion_test() {
+        let v = Vector4::new(1.5, -2.0, 3.25, 1.0);
+        assert_eq!(v.to_array(), [1.5, -2.0, 3.25, 1.0]);
+        assert_eq!(Vector4::from_array(v.to_array()), v);
+    }
+
+    #[test]
+    fn tuple_conversion_test() {
+        let v = Vector4::from((1.5, -2.0, 3.25, 1.0));
+        assert_eq!(v, Vector4::new(1.5, -2.0, 3.25, 1.0));
+
+        let tuple: (f32, f32, f32, f32) = v.into();
+        assert_eq!(tuple, (1.5, -2.0, 3.25, 1.0));
+    }
+
+    #[test]
+    fn default_test() {
+        assert_eq!(Vector4::default(), Vector4::new(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn display_test() {
+        let v = Vector4::new(1, 2, 3, 1);
+        assert_eq!(v.to_string(), "(1, 2, 3, 1)");
+        assert_eq!(format!("{v:.1}"), "(1.0, 2.0, 3.0, 1.0)");
+    }
+
+    #[test]
+    fn index_mut_test() {
+        let mut v = Vector4::new(0, 0, 0, 0);
+        for i in 0..4 {
+            v[i] = i as f32 + 1.0;
+        }
+        assert_eq!(v, Vector4::new(1, 2, 3, 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn index_mut_out_of_range_test() {
+        let mut v = Vector4::new(0, 0, 0, 0);
+        v[4] = 1.0;
+    }
+
+    #[test]
+    fn approx_eq_test() {
+        let a = Vector4::new(1, 2, 3, 1);
+        let b = Vector4::new(1.0, 2.0, 3.0 + 1e-6, 1.0);
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&b, 1e-7));
+    }
+
+    #[test]
+    fn min_max_test() {
+        let a = Vector4::new(-1.5, 4, 0, 1);
+        let b = Vector4::new(2, -3, -0.5, 1);
+        assert_eq!(a.min(b), Vector4::new(-1.5, -3, -0.5, 1));
+        assert_eq!(a.max(b), Vector4::new(2, 4, 0, 1));
+    }
+
+    #[test]
+    fn rounding_test() {
+        let v = Vector4::new(-1.5, 2.4, -0.6, 7.5);
+        assert_eq!(v.abs(), Vector4::new(1.5, 2.4, 0.6, 7.5));
+        assert_eq!(v.floor(), Vector4::new(-2, 2, -1, 7));
+        assert_eq!(v.ceil(), Vector4::new(-1, 3, 0, 8));
+        assert_eq!(v.round(), Vector4::new(-2, 2, -1, 8));
+    }
+
+    #[test]
+    fn try_normalize_test() {
+        assert_abs_diff_eq!(
+            Vector4::new(0, 0, 3, 4).try_normalize().unwrap(),
+            Vector4::new(0, 0, 0.6, 0.8)
+        );
+        assert_eq!(Vector4::new(0, 0, 0, 0).try_normalize(), None);
+        assert_eq!(Vector4::new(1e-8, 0, 0, 1e-8).try_normalize(), None);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let v = Vector4::new(1, 2, 3, 1);
+        assert_eq!(v.components().collect::<Vec<_>>(), vec![1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(v.into_iter().len(), 4);
+
+        let points = [Vector4::new(1, 0, 0, 1), Vector4::new(0, 3, -2, 1)];
+        assert_eq!(points.iter().sum::<Vector4>(), Vector4::new(1, 3, -2, 2));
+        assert_eq!(
+            points.into_iter().sum::<Vector4>() / 2.0,
+            Vector4::new(0.5, 1.5, -1, 1)
+        );
+    }
 }
@@ -1,31 +1,51 @@
-use crate::{Vector2, model::{Model, VertexData}, vector::vector3::Vector3};
+use crate::{
+    Vector2,
+    model::{Model, ModelError, VertexData},
+    vector::vector3::Vector3,
+};
 
 #[derive(Debug)]
 pub struct Vertex<'a> {
     pub pos: &'a Vector3,
     pub tex_coord: Option<&'a Vector2>,
     pub normal: Option<&'a Vector3>,
+    // Clip space w from before the perspective divide, 1.0 for unprojected vertices
+    pub w: f32,
 }
 
 impl<'a> Vertex<'a> {
-    pub fn new(data: &'a VertexData, model: &'a Model) -> Vertex<'a> {
-        let VertexData { pos, tex_coord, normal } = data;
-        let pos = model.vertices.get(data.pos - 1).unwrap();
-        
-        let tex_coord = match tex_coord {
-            Some(index) => Some(model.tex_coords.get(*index - 1).expect("Expected valid tex coord index")),
-            None => None,
-        };  
-        
-        let normal: Option<&Vector3> = match normal {
-            Some(index) => Some(model.normals.get(*index - 1).expect("Expected valid tex coord index")),
-            None => None,
-        };
+    // Looks up the attributes a face vertex refers to, failing on indices that are out of range
+    pub fn new(data: &'a VertexData, model: &'a Model) -> Result<Vertex<'a>, ModelError> {
+        let VertexData {
+            pos,
+            tex_coord,
+            normal,
+        } = *data;
+        let pos = Vertex::lookup(&model.vertices, pos, "vertices")?;
+        let tex_coord = tex_coord
+            .map(|index| Vertex::lookup(&model.tex_coords, index, "tex_coords"))
+            .transpose()?;
+        let normal = normal
+            .map(|index| Vertex::lookup(&model.normals, index, "normals"))
+            .transpose()?;
 
-        Vertex {
+        Ok(Vertex {
             pos,
             tex_coord,
-            normal
-        }
+            normal,
+            w: 1.0,
+        })
     }
-}
\ No newline at end of file
+
+    // Obj indices are 1-based
+    pub(crate) fn lookup<T>(
+        items: &'a [T],
+        index: usize,
+        array: &'static str,
+    ) -> Result<&'a T, ModelError> {
+        index
+            .checked_sub(1)
+            .and_then(|i| items.get(i))
+            .ok_or(ModelError::OutOfRange { array, index })
+    }
+}